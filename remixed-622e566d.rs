@@ -3,11 +3,13 @@
 // Autor: Claude AI
 // Descrição: Sistema de organização semanal com blocos de 30 minutos
 
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Local, NaiveTime, Weekday};
+use chrono::{DateTime, Datelike, Local, NaiveTime, Timelike, Weekday};
 use uuid::Uuid;
 
 // Estruturas de dados
@@ -17,6 +19,12 @@ pub struct Category {
     pub color: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub text: String,
+    pub created_at: DateTime<Local>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Activity {
     pub id: String,
@@ -26,8 +34,151 @@ pub struct Activity {
     pub start_time: String, // Formato "HH:MM"
     pub location: Option<String>,
     pub description: Option<String>,
-    pub day: String,
+    #[serde(with = "day_serde")]
+    pub day: Weekday, // Gravado pelo nome canônico ("Segunda"); ver Locale para exibição
     pub created_at: DateTime<Local>,
+    #[serde(default)]
+    pub notes: Vec<Note>, // Histórico de anotações ("movida de terça por causa de X")
+    #[serde(default)]
+    pub effort: Option<u8>, // Nível de esforço/energia de 1 a 5
+    #[serde(default)]
+    pub depends_on: Option<String>, // ID da atividade que precisa terminar antes desta começar
+    #[serde(default)]
+    pub status: ActivityStatus,
+    #[serde(default)]
+    pub series_id: Option<String>, // Ocorrências criadas juntas por add_recurring_activity
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>, // Regra que gerou a série
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub tags: Vec<String>, // Marcadores livres ("remoto", "faturável"), em minúsculas e sem repetição
+    #[serde(default)]
+    pub reminder_minutes: Option<u32>, // Antecedência do lembrete em relação ao início
+}
+
+// Regra de repetição de uma atividade recorrente
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Recurrence {
+    Daily,                       // Todos os dias da semana
+    Weekly {
+        #[serde(with = "day_serde::list")]
+        days: Vec<Weekday>,
+    }, // Nos dias informados
+    EveryNWeeks(u32),            // No dia da atividade, a cada N semanas (a agenda guarda uma semana)
+}
+
+impl Recurrence {
+    // Dias da semana em que a regra gera ocorrências (`day` é o dia pedido para a atividade)
+    fn days(&self, day: Weekday) -> Vec<Weekday> {
+        match self {
+            Recurrence::Daily => WEEKDAYS.to_vec(),
+            Recurrence::Weekly { days } => days.clone(),
+            Recurrence::EveryNWeeks(_) => vec![day],
+        }
+    }
+    
+    // Descrição curta para listagens
+    pub fn describe(&self) -> String {
+        match self {
+            Recurrence::Daily => "Todos os dias".to_string(),
+            Recurrence::Weekly { days } => format!("Toda semana: {}", days.iter().map(|d| day_name(*d)).collect::<Vec<_>>().join(", ")),
+            Recurrence::EveryNWeeks(n) => format!("A cada {} semana(s)", n),
+        }
+    }
+}
+
+// Situação de uma atividade (dados antigos sem o campo são confirmados)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ActivityStatus {
+    #[default]
+    Confirmed,
+    Tentative, // "Talvez": aparece marcada com "~" na grade
+    Cancelled, // Fica guardada, mas some das listagens, da grade e das estatísticas
+}
+
+// Prioridade de uma atividade (dados antigos sem o campo ficam como Normal).
+// A ordem das variantes é a ordem de importância: Low < Normal < High < Critical
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
+impl Priority {
+    // Nome para exibição
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::Low => "Baixa",
+            Priority::Normal => "Normal",
+            Priority::High => "Alta",
+            Priority::Critical => "Crítica",
+        }
+    }
+}
+
+// Conjunto de campos para search_in (combine com |)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchFields(u8);
+
+impl SearchFields {
+    pub const TITLE: Self = Self(1);
+    pub const CATEGORY: Self = Self(1 << 1);
+    pub const LOCATION: Self = Self(1 << 2);
+    pub const DESCRIPTION: Self = Self(1 << 3);
+    pub const TAGS: Self = Self(1 << 4);
+    pub const ALL: Self = Self(0b1_1111);
+    
+    // Todos os campos de `other` estão no conjunto
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for SearchFields {
+    type Output = Self;
+    
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+// Ordem de get_all_activities_sorted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ActivityOrder {
+    #[default]
+    DayAndTime,        // Dia da semana, depois horário
+    PriorityThenTime,  // Mais importantes primeiro; empates por dia e horário
+}
+
+impl Activity {
+    // Início em minutos desde a meia-noite (None se start_time for inválido)
+    pub fn start_minutes(&self) -> Option<i32> {
+        parse_minutes(&self.start_time)
+    }
+    
+    // Término em minutos desde a meia-noite do dia de início (pode passar de 24:00;
+    // o excedente ocupa o início do dia seguinte)
+    pub fn end_minutes(&self) -> Option<i32> {
+        Some(self.start_minutes()? + duration_minutes(self.duration))
+    }
+    
+    // Horário de término "HH:MM" (vazio se start_time for inválido). Passando da
+    // meia-noite, é o horário no dia seguinte (23:30 + 1h → "00:30")
+    pub fn end_time(&self) -> String {
+        self.end_naive_time()
+            .map(|time| time.format("%H:%M").to_string())
+            .unwrap_or_default()
+    }
+    
+    // Horário de término como NaiveTime, com a mesma volta da meia-noite de end_time
+    pub fn end_naive_time(&self) -> Option<NaiveTime> {
+        let minutes = self.end_minutes()?.rem_euclid(24 * 60) as u32;
+        NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,471 +187,4955 @@ pub struct WeeklyStats {
     pub by_category: HashMap<String, f32>,
     pub by_day: HashMap<String, f32>,
     pub activity_count: usize,
+    #[serde(default)]
+    pub effort_by_day: HashMap<String, u32>,
+    #[serde(default)]
+    pub free_time: f32, // Horas ainda livres na janela da grade (sem horários de silêncio)
+    #[serde(default)]
+    pub utilization_percent: f32,
+    #[serde(default)]
+    pub busiest_day: Option<String>,
+    #[serde(default)]
+    pub quietest_day: Option<String>,
+}
+
+// Categoria no relatório de estatísticas em JSON (export_stats_json)
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryStats {
+    pub key: String,
+    pub name: String,
+    pub color: String, // Já resolvida (cor de reserva se a da categoria for inválida)
+    pub hours: f32,
+    pub percentage: f32, // Mesmo arredondamento de display_stats (soma 100%)
+}
+
+// Estatísticas semanais prontas para dashboards: os campos de WeeklyStats mais as categorias
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsReport {
+    #[serde(flatten)]
+    pub stats: WeeklyStats,
+    pub categories: Vec<CategoryStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyStatsMinutes {
+    pub total_minutes: u32,
+    pub by_category: HashMap<String, u32>,
+    pub by_day: HashMap<String, u32>,
+    pub activity_count: usize,
+}
+
+// Progresso de uma meta semanal (mínimo de horas) de uma categoria
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalStatus {
+    pub category: String,
+    pub target_hours: f32,
+    pub actual_hours: f32,
+    pub remaining_hours: f32,
+    pub percentage: f32,
+}
+
+// O que a remoção de uma atividade liberaria na agenda
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovalImpact {
+    pub freed_hours: f32,
+    pub free_gap: Option<(String, String)>, // Intervalo livre contínuo resultante no dia ("HH:MM", "HH:MM")
+    pub waitlist_fits: Vec<usize>,          // Índices da lista de espera que passariam a caber
+}
+
+// Alterações opcionais aplicadas por edit_activity_with ou em lote por edit_matching (None mantém o valor atual)
+#[derive(Debug, Clone, Default)]
+pub struct ActivityPatch {
+    pub title: Option<String>,
+    pub category: Option<String>,
+    pub day: Option<Weekday>,
+    pub start_time: Option<String>,
+    pub duration: Option<f32>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub effort: Option<Option<u8>>, // Some(None) remove o esforço
+    pub status: Option<ActivityStatus>,
+    pub priority: Option<Priority>,
+    pub tags: Option<Vec<String>>, // Substitui a lista inteira
+    pub reminder_minutes: Option<Option<u32>>, // Some(None) remove o lembrete
+}
+
+impl ActivityPatch {
+    // Aplicar os campos preenchidos à atividade
+    fn apply(&self, activity: &mut Activity) {
+        if let Some(t) = &self.title { activity.title = t.clone(); }
+        if let Some(c) = &self.category { activity.category = c.clone(); }
+        if let Some(d) = self.day { activity.day = d; }
+        if let Some(st) = &self.start_time { activity.start_time = st.clone(); }
+        if let Some(dur) = self.duration { activity.duration = dur; }
+        if let Some(loc) = &self.location { activity.location = Some(loc.clone()); }
+        if let Some(desc) = &self.description { activity.description = Some(desc.clone()); }
+        if let Some(effort) = self.effort { activity.effort = effort; }
+        if let Some(status) = self.status { activity.status = status; }
+        if let Some(priority) = self.priority { activity.priority = priority; }
+        if let Some(tags) = &self.tags { activity.tags = normalize_tags(tags); }
+        if let Some(minutes) = self.reminder_minutes { activity.reminder_minutes = minutes; }
+    }
+}
+
+// Cópia do estado do organizador em memória (snapshot/restore); nunca é gravada em disco
+#[derive(Debug, Clone)]
+pub struct OrganizerSnapshot {
+    activities: Vec<Activity>,
+    categories: HashMap<String, Category>,
+    category_keywords: Vec<(String, String)>,
+    quiet_hours: Vec<(String, String)>,
+    waitlist: Vec<ActivityParams>,
+    goals: HashMap<String, f32>,
+    stats_precision: usize,
+    fallback_color: String,
+    grid_collision_display: GridCollisionDisplay,
+    title_normalization: TitleNormalization,
+    working_hours: (i32, i32),
+    slot_minutes: u32,
+    align_to_slots: bool,
+    tentative_blocks: bool,
+    active_schedule: String,
+    schedules: HashMap<String, Vec<Activity>>,
+}
+
+// Atividade sem horário fixo para auto_schedule (dias vazios = qualquer dia da semana)
+#[derive(Debug, Clone, Default)]
+pub struct FlexibleActivity {
+    pub title: String,
+    pub category: String,
+    pub duration: f32,
+    pub days: Vec<Weekday>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+}
+
+// Como auto_schedule escolhe o horário entre os slots livres
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PackStrategy {
+    #[default]
+    EarliestFit,  // Primeiro slot livre, na ordem da semana
+    SpreadEvenly, // Dia com menos tempo agendado, no slot mais cedo
+    PackTight,    // Slot colado a outra atividade, deixando menos buracos
+}
+
+// Formatos de arquivo aceitos pelas exportações em lote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    // Extensão de arquivo do formato
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+// O que import_from_csv faz com as atividades já cadastradas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    Append,  // Mantém as atuais e acrescenta as importadas
+    Replace, // Substitui as atuais pelas importadas
+}
+
+// Resultado de import_from_csv
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub rejected: Vec<(usize, String)>, // (número do registro no arquivo, motivo)
+}
+
+// Resultado de snap_to_slots
+#[derive(Debug, Clone, Default)]
+pub struct SnapReport {
+    pub adjusted: Vec<String>,            // IDs das atividades que mudaram de horário
+    pub conflicts: Vec<(String, String)>, // (ID ajustado, ID da outra) que passaram a se sobrepor
+}
+
+// Diferença entre a agenda em memória e a salva no arquivo
+#[derive(Debug, Clone)]
+pub enum Change {
+    Added(Activity),
+    Removed(Activity),
+    Modified { before: Box<Activity>, after: Box<Activity> },
+}
+
+// Atividade de um modelo de semana: sem ID, data de criação, anotações nem vínculos
+// (dependência, série), que só fazem sentido na agenda de origem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TemplateActivity {
+    title: String,
+    category: String,
+    #[serde(with = "day_serde")]
+    day: Weekday,
+    start_time: String,
+    duration: f32,
+    location: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    effort: Option<u8>,
+    #[serde(default)]
+    status: ActivityStatus,
+    #[serde(default)]
+    priority: Priority,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    reminder_minutes: Option<u32>,
+}
+
+impl TemplateActivity {
+    fn from_activity(activity: &Activity) -> Self {
+        TemplateActivity {
+            title: activity.title.clone(),
+            category: activity.category.clone(),
+            day: activity.day,
+            start_time: activity.start_time.clone(),
+            duration: activity.duration,
+            location: activity.location.clone(),
+            description: activity.description.clone(),
+            effort: activity.effort,
+            status: activity.status,
+            priority: activity.priority,
+            tags: activity.tags.clone(),
+            reminder_minutes: activity.reminder_minutes,
+        }
+    }
+    
+    // Montar uma atividade nova (ID e data de criação gerados agora)
+    fn to_activity(&self) -> Activity {
+        Activity {
+            id: Uuid::new_v4().to_string(),
+            title: self.title.clone(),
+            category: self.category.clone(),
+            duration: self.duration,
+            start_time: self.start_time.clone(),
+            location: self.location.clone(),
+            description: self.description.clone(),
+            day: self.day,
+            created_at: Local::now(),
+            notes: Vec::new(),
+            effort: self.effort,
+            depends_on: None,
+            status: self.status,
+            series_id: None,
+            recurrence: None,
+            priority: self.priority,
+            tags: self.tags.clone(),
+            reminder_minutes: self.reminder_minutes,
+        }
+    }
+}
+
+// Arquivo de modelo (templates/<nome>.json)
+#[derive(Serialize, Deserialize)]
+struct TemplateFile {
+    activities: Vec<TemplateActivity>,
+}
+
+// Dados de uma atividade ainda sem ID (ex.: na lista de espera)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityParams {
+    pub title: String,
+    pub category: String,
+    #[serde(with = "day_serde")]
+    pub day: Weekday,
+    pub start_time: String,
+    pub duration: f32,
+    pub location: Option<String>,
+    pub description: Option<String>,
+}
+
+impl ActivityParams {
+    // Montar uma atividade nova (ID e data de criação gerados agora)
+    fn to_activity(&self) -> Activity {
+        Activity {
+            id: Uuid::new_v4().to_string(),
+            title: self.title.clone(),
+            category: self.category.clone(),
+            duration: self.duration,
+            start_time: self.start_time.clone(),
+            location: self.location.clone(),
+            description: self.description.clone(),
+            day: self.day,
+            created_at: Local::now(),
+            notes: Vec::new(),
+            effort: None,
+            depends_on: None,
+            status: ActivityStatus::default(),
+            series_id: None,
+            recurrence: None,
+            priority: Priority::default(),
+            tags: Vec::new(),
+            reminder_minutes: None,
+        }
+    }
+}
+
+// Erros de validação e edição de atividades; a mensagem (Display) é a mesma exibida pela CLI
+#[derive(Debug, Clone, PartialEq)]
+pub enum WeeklyError {
+    InvalidDay(String),
+    InvalidTime(String),
+    UnalignedTime { time: String, slot_minutes: u32 },
+    UnknownCategory(String),
+    TimeConflict { with: Vec<String> }, // Títulos de todas as atividades em conflito
+    DurationOutOfRange { duration: f32, min: f32 },
+    DurationNotMultipleOfSlot { duration: f32, slot_minutes: u32 },
+    EmptyTitle,
+    EffortOutOfRange(u8),
+    QuietHours { from: String, to: String },
+    SelfDependency,
+    DependencyNotFound(String),
+    StartsBeforeDependency { activity: String, dependency: String },
+    DependentWouldStartEarlier { dependent: String },
+    ActivityNotFound,
+    Rule(String), // Erro devolvido por uma regra de add_validator
+    TemplateNotFound(String),
+    InvalidTemplate { name: String, reason: String },
+    TemplateConflicts(Vec<String>), // Uma linha por atividade do modelo que não coube
+    BatchItem { index: usize, title: String, error: Box<WeeklyError> }, // Item de add_activities que falhou
+}
+
+impl std::fmt::Display for WeeklyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeeklyError::InvalidDay(day) => write!(f, "Dia inválido: {}. Use: {}", day, WEEK_DAYS.join(", ")),
+            WeeklyError::InvalidTime(time) => write!(f, "Horário inválido: {}", time),
+            WeeklyError::UnalignedTime { time, slot_minutes } => write!(f, "Horário fora da grade: {}. Use múltiplos de {} minutos", time, slot_minutes),
+            WeeklyError::UnknownCategory(category) => write!(f, "Categoria '{}' não existe", category),
+            WeeklyError::TimeConflict { with } => write!(f, "Conflito de horário com: '{}'", with.join("', '")),
+            WeeklyError::DurationOutOfRange { min, .. } => write!(f, "Duração deve ser entre {} e 8 horas", min),
+            WeeklyError::DurationNotMultipleOfSlot { duration, slot_minutes } => write!(f, "Duração deve ser múltipla de {} minutos (recebido {})", slot_minutes, duration),
+            WeeklyError::EmptyTitle => write!(f, "Título não pode estar vazio"),
+            WeeklyError::EffortOutOfRange(effort) => write!(f, "Esforço deve ser entre 1 e 5 (recebido {})", effort),
+            WeeklyError::QuietHours { from, to } => write!(f, "Horário de silêncio: nada pode ser agendado entre {} e {}", from, to),
+            WeeklyError::SelfDependency => write!(f, "Uma atividade não pode depender de si mesma"),
+            WeeklyError::DependencyNotFound(id) => write!(f, "Dependência não encontrada: {}", id),
+            WeeklyError::StartsBeforeDependency { activity, dependency } => write!(f, "'{}' precisa começar depois que '{}' terminar", activity, dependency),
+            WeeklyError::DependentWouldStartEarlier { dependent } => write!(f, "'{}' depende desta atividade e ficaria antes do seu término", dependent),
+            WeeklyError::ActivityNotFound => write!(f, "Atividade não encontrada"),
+            WeeklyError::Rule(message) => write!(f, "{}", message),
+            WeeklyError::TemplateNotFound(name) => write!(f, "Modelo '{}' não encontrado", name),
+            WeeklyError::InvalidTemplate { name, reason } => write!(f, "Não foi possível ler o modelo '{}': {}", name, reason),
+            WeeklyError::TemplateConflicts(problems) => write!(f, "O modelo não foi aplicado:\n  - {}", problems.join("\n  - ")),
+            WeeklyError::BatchItem { index, title, error } => write!(f, "Nenhuma atividade adicionada: item {} ('{}'): {}", index + 1, title, error),
+        }
+    }
+}
+
+impl std::error::Error for WeeklyError {}
+
+// Permite usar `?` com WeeklyError nos métodos que ainda retornam Result<_, String>
+impl From<WeeklyError> for String {
+    fn from(error: WeeklyError) -> Self {
+        error.to_string()
+    }
+}
+
+// Regra de negócio personalizada executada após as validações padrão
+pub type ActivityValidator = Box<dyn Fn(&Activity) -> Result<(), String>>;
+
+// Versão atual do formato do arquivo de dados (arquivos sem o campo são anteriores à 1)
+const SAVE_VERSION: u32 = 2;
+
+// Agenda ativa de arquivos anteriores às agendas nomeadas (e de organizadores novos)
+const DEFAULT_SCHEDULE: &str = "padrão";
+
+// Formato do arquivo de dados (também usado pelos arquivos de histórico)
+#[derive(Serialize, Deserialize)]
+struct SaveData {
+    #[serde(default)]
+    version: u32,
+    activities: Vec<Activity>,
+    categories: HashMap<String, Category>,
+    #[serde(default)]
+    category_keywords: Option<Vec<(String, String)>>,
+    #[serde(default)]
+    quiet_hours: Vec<(String, String)>,
+    #[serde(default)]
+    waitlist: Vec<ActivityParams>,
+    #[serde(default)]
+    goals: HashMap<String, f32>,
+    #[serde(default)]
+    working_hours: Option<(i32, i32)>, // (start_hour, end_hour); ausente = janela padrão
+    #[serde(default)]
+    slot_minutes: Option<u32>, // Granularidade da grade; ausente = DEFAULT_SLOT_MINUTES
+    #[serde(default)]
+    active_schedule: Option<String>, // Dona de `activities`; ausente = DEFAULT_SCHEDULE
+    #[serde(default)]
+    schedules: HashMap<String, Vec<Activity>>, // Demais agendas nomeadas
+}
+
+impl SaveData {
+    // Atividades gravadas de uma agenda, ativa ou não (vazio se ela não existir no arquivo)
+    fn schedule_activities(mut self, name: &str) -> Vec<Activity> {
+        if self.active_schedule.as_deref().unwrap_or(DEFAULT_SCHEDULE) == name {
+            self.activities
+        } else {
+            self.schedules.remove(name).unwrap_or_default()
+        }
+    }
+}
+
+// Arquivo de dados como lido do disco, antes de ser convertido para o formato atual
+struct RawSaveData {
+    version: u32,
+    content: serde_json::Value,
+}
+
+impl RawSaveData {
+    // Ler o JSON sem assumir nenhum formato além de ser um objeto
+    fn parse(content: &str) -> Result<Self, String> {
+        let content: serde_json::Value = serde_json::from_str(content)
+            .map_err(|e| format!("arquivo corrompido: {}", e))?;
+        let version = content.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        Ok(RawSaveData { version, content })
+    }
+}
+
+// Atualizar um arquivo de versão anterior para o formato atual
+fn migrate(data: RawSaveData) -> Result<SaveData, String> {
+    if data.version > SAVE_VERSION {
+        return Err(format!(
+            "arquivo na versão {} do formato, mas esta versão do programa só lê até a {}. Atualize o programa",
+            data.version, SAVE_VERSION
+        ));
+    }
+    
+    // 0 → 1: mesmo layout, só passa a ter o campo `version`
+    // 1 → 2: agendas nomeadas; sem os campos novos, `activities` vira a agenda "padrão"
+    let mut save: SaveData = serde_json::from_value(data.content)
+        .map_err(|e| format!("arquivo corrompido: {}", e))?;
+    
+    // Valores editados à mão passam pelas mesmas regras dos setters (0 travaria a grade)
+    if let Some((start_hour, end_hour)) = save.working_hours {
+        check_working_hours(start_hour, end_hour).map_err(|e| format!("arquivo corrompido: {}", e))?;
+    }
+    if let Some(slot_minutes) = save.slot_minutes {
+        check_slot_minutes(slot_minutes).map_err(|e| format!("arquivo corrompido: {}", e))?;
+    }
+    
+    save.version = SAVE_VERSION;
+    Ok(save)
+}
+
+// Janela diária válida: começa entre 0h e 23h, termina até 24h e depois do início
+fn check_working_hours(start_hour: i32, end_hour: i32) -> Result<(), String> {
+    if !(0..24).contains(&start_hour) || !(1..=24).contains(&end_hour) || start_hour >= end_hour {
+        return Err(format!("Janela inválida: {}h às {}h", start_hour, end_hour));
+    }
+    Ok(())
+}
+
+// Granularidade válida da grade (uma de ALLOWED_SLOT_MINUTES)
+fn check_slot_minutes(slot_minutes: u32) -> Result<(), String> {
+    if !ALLOWED_SLOT_MINUTES.contains(&slot_minutes) {
+        return Err(format!("Granularidade inválida: {} minutos. Use 15, 30 ou 60", slot_minutes));
+    }
+    Ok(())
+}
+
+// Ler e migrar o conteúdo de um arquivo de dados ou de histórico
+fn parse_save_data(content: &str) -> Result<SaveData, String> {
+    migrate(RawSaveData::parse(content)?)
+}
+
+// Uma linha da grade semanal: o horário e, para cada dia, o texto da célula e a categoria usada para colorir
+type GridRow<'a> = (String, Vec<(String, Option<&'a str>)>);
+
+// Como a grade semanal mostra várias atividades começando no mesmo horário
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GridCollisionDisplay {
+    Count,          // Apenas o indicador, ex.: "⚠2"
+    #[default]
+    CountAndTitle,  // Indicador seguido do início do primeiro título, ex.: "⚠2 Reunião"
+}
+
+// Como os títulos são normalizados ao adicionar/editar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TitleNormalization {
+    #[default]
+    Trim,           // Apenas remove espaços das pontas
+    CollapseSpaces, // Também troca sequências de espaços internos por um só
+    TitleCase,      // Também deixa cada palavra com a inicial maiúscula, ex.: "Reunião De Equipe"
+}
+
+impl TitleNormalization {
+    // Aplicar a normalização a um título
+    pub fn apply(&self, title: &str) -> String {
+        match self {
+            TitleNormalization::Trim => title.trim().to_string(),
+            TitleNormalization::CollapseSpaces => title.split_whitespace().collect::<Vec<_>>().join(" "),
+            TitleNormalization::TitleCase => title.split_whitespace()
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
+                        None => String::new(),
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(" "),
+        }
+    }
 }
 
 pub struct WeeklyOrganizer {
     activities: Vec<Activity>,
     categories: HashMap<String, Category>,
     data_file: String,
+    stats_precision: usize, // Casas decimais nas porcentagens das estatísticas
+    category_keywords: Vec<(String, String)>, // Palavra-chave no título → chave da categoria
+    fallback_color: String, // Cor usada quando a categoria não tem uma cor #RRGGBB válida
+    autosave: bool, // Salvar no disco a cada alteração
+    dirty: Cell<bool>, // Há alterações ainda não gravadas no arquivo
+    day_index: RefCell<Option<HashMap<Weekday, Vec<usize>>>>, // Posições em `activities` por dia; None = refazer na próxima consulta
+    validators: Vec<ActivityValidator>,
+    quiet_hours: Vec<(String, String)>, // Faixas "HH:MM"–"HH:MM" bloqueadas (podem virar a meia-noite)
+    grid_collision_display: GridCollisionDisplay,
+    waitlist: Vec<ActivityParams>, // Atividades que não couberam e aguardam um horário livre
+    goals: HashMap<String, f32>, // Categoria → mínimo de horas desejado na semana
+    title_normalization: TitleNormalization,
+    tentative_blocks: bool, // Atividades provisórias ocupam o horário na checagem de conflitos
+    align_to_slots: bool, // Exigir início em um slot da grade (a grade só mostra esses horários)
+    start_hour: i32, // Janela diária da grade: primeiro slot às start_hour:00,
+    end_hour: i32,   // último às end_hour:00 menos um slot
+    slot_minutes: u32, // Granularidade da grade: 15, 30 ou 60 minutos
+    ansi_colors: bool, // Colorir títulos no terminal com a cor da categoria
+    locale: Locale, // Idioma dos dias na exibição e na entrada (o armazenamento é sempre canônico)
+    active_schedule: String, // Agenda cujas atividades estão em `activities`
+    schedules: HashMap<String, Vec<Activity>>, // Demais agendas (ex.: "viagem"), fora da ativa
+    undo_stack: Vec<HistoryState>, // Estados anteriores a adicionar/editar/mover/remover (só nesta sessão)
+    redo_stack: Vec<HistoryState>,
 }
 
-impl WeeklyOrganizer {
-    // Construtor
-    pub fn new(data_file: &str) -> Self {
-        let mut organizer = WeeklyOrganizer {
-            activities: Vec::new(),
-            categories: HashMap::new(),
-            data_file: data_file.to_string(),
-        };
+// Estado guardado no histórico de desfazer/refazer (a lista de espera muda quando uma
+// remoção encaixa itens dela)
+#[derive(Debug, Clone)]
+struct HistoryState {
+    activities: Vec<Activity>,
+    waitlist: Vec<ActivityParams>,
+}
+
+// Quantos passos undo consegue voltar
+const UNDO_LIMIT: usize = 50;
+
+// Janela diária padrão da grade (6h às 23h, último slot às 22h30)
+const DEFAULT_START_HOUR: i32 = 6;
+const DEFAULT_END_HOUR: i32 = 23;
+
+// Granularidade padrão da grade e as permitidas por set_slot_minutes
+const DEFAULT_SLOT_MINUTES: u32 = 30;
+const ALLOWED_SLOT_MINUTES: [u32; 3] = [15, 30, 60];
+
+// Soma diária de esforço (1–5 por atividade) a partir da qual display_stats alerta
+const HIGH_DAILY_EFFORT: u32 = 15;
+
+// Dias da semana na ordem de exibição. Os nomes são a forma canônica gravada no arquivo
+// de dados, qualquer que seja o idioma de exibição (Locale)
+const WEEK_DAYS: [&str; 7] = ["Segunda", "Terça", "Quarta", "Quinta", "Sexta", "Sábado", "Domingo"];
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun,
+];
+
+// Nome canônico de um dia ("Segunda")
+pub fn day_name(day: Weekday) -> &'static str {
+    WEEK_DAYS[day.num_days_from_monday() as usize]
+}
+
+// Dia a partir do nome canônico (sem diferenciar maiúsculas) ou do nome em inglês aceito
+// pelo chrono ("Mon", "monday"). Para nomes no idioma de exibição, use Locale::parse_day
+pub fn parse_day(name: &str) -> Option<Weekday> {
+    let name = name.trim();
+    WEEK_DAYS.iter()
+        .position(|d| d.to_lowercase() == name.to_lowercase())
+        .map(|index| WEEKDAYS[index])
+        .or_else(|| name.parse().ok())
+}
+
+// (De)serialização de Weekday pelo nome canônico, o mesmo texto dos arquivos em que o dia
+// ainda era uma String livre; na leitura também aceita os nomes em inglês
+mod day_serde {
+    use super::{day_name, parse_day};
+    use chrono::Weekday;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    
+    pub fn serialize<S: Serializer>(day: &Weekday, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(day_name(*day))
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Weekday, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        parse_day(&name).ok_or_else(|| D::Error::custom(format!("dia inválido: {}", name)))
+    }
+    
+    // O mesmo para listas de dias
+    pub mod list {
+        use super::*;
+        
+        pub fn serialize<S: Serializer>(days: &[Weekday], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(days.iter().map(|day| day_name(*day)))
+        }
+        
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Weekday>, D::Error> {
+            Vec::<String>::deserialize(deserializer)?.iter()
+                .map(|name| parse_day(name).ok_or_else(|| D::Error::custom(format!("dia inválido: {}", name))))
+                .collect()
+        }
+    }
+}
+
+// Idioma de exibição dos dias. A entrada aceita os nomes do idioma ou os aceitos por parse_day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    day_names: [&'static str; 7],       // Na ordem de WEEK_DAYS (segunda a domingo)
+    short_day_names: [&'static str; 7],
+}
+
+impl Locale {
+    pub fn pt_br() -> Self {
+        Locale {
+            day_names: WEEK_DAYS,
+            short_day_names: ["Seg", "Ter", "Qua", "Qui", "Sex", "Sáb", "Dom"],
+        }
+    }
+    
+    pub fn en_us() -> Self {
+        Locale {
+            day_names: ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"],
+            short_day_names: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+        }
+    }
+    
+    // Nomes dos dias, de segunda a domingo
+    pub fn day_names(&self) -> &[&'static str; 7] {
+        &self.day_names
+    }
+    
+    // Nome de exibição de um dia
+    pub fn day_name(&self, day: Weekday) -> &'static str {
+        self.day_names[day.num_days_from_monday() as usize]
+    }
+    
+    // Abreviação de exibição de um dia
+    pub fn short_day_name(&self, day: Weekday) -> &'static str {
+        self.short_day_names[day.num_days_from_monday() as usize]
+    }
+    
+    // Dia digitado no idioma (nome ou abreviação, sem diferenciar maiúsculas) ou em uma
+    // das formas aceitas por parse_day
+    pub fn parse_day(&self, name: &str) -> Option<Weekday> {
+        let lowered = name.trim().to_lowercase();
+        (0..WEEKDAYS.len())
+            .find(|&i| {
+                [self.day_names[i], self.short_day_names[i]].iter()
+                    .any(|candidate| candidate.to_lowercase() == lowered)
+            })
+            .map(|i| WEEKDAYS[i])
+            .or_else(|| parse_day(name))
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::pt_br()
+    }
+}
+
+// Tags sem espaços nas pontas, em minúsculas, sem vazias nem repetidas (mantém a ordem)
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+// Converter "HH:MM" em minutos desde a meia-noite
+fn parse_minutes(time: &str) -> Option<i32> {
+    NaiveTime::parse_from_str(time, "%H:%M").ok()
+        .map(|t| t.hour() as i32 * 60 + t.minute() as i32)
+}
+
+// Distribuir porcentagens arredondadas que somam exatamente 100% (maiores restos)
+fn apportion_percentages(values: &[f32], decimals: usize) -> Vec<f32> {
+    let total: f32 = values.iter().sum();
+    if total <= 0.0 {
+        return vec![0.0; values.len()];
+    }
+    
+    let scale = 10f64.powi(decimals as i32);
+    let target_units = (100.0 * scale).round() as i64;
+    let raw: Vec<f64> = values.iter()
+        .map(|v| *v as f64 / total as f64 * 100.0 * scale)
+        .collect();
+    let mut units: Vec<i64> = raw.iter().map(|r| r.floor() as i64).collect();
+    
+    // Entregar as unidades que faltam aos maiores restos
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| (raw[b] - raw[b].floor()).partial_cmp(&(raw[a] - raw[a].floor())).unwrap());
+    let missing = target_units - units.iter().sum::<i64>();
+    for &i in order.iter().take(missing.max(0) as usize) {
+        units[i] += 1;
+    }
+    
+    units.iter().map(|u| (*u as f64 / scale) as f32).collect()
+}
+
+// Ler uma cor no formato "#RRGGBB"
+fn parse_hex_color(hex: &str) -> Option<(u8, u8, u8)> {
+    let digits = hex.strip_prefix('#')?;
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+// Cor mais próxima na paleta de 256 cores do terminal (cubo 6x6x6, índices 16–231)
+fn ansi_256(hex: &str) -> Option<u8> {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let nearest = |channel: u8| {
+        (0..LEVELS.len()).min_by_key(|&i| (LEVELS[i] as i32 - channel as i32).abs()).unwrap() as u8
+    };
+    let (r, g, b) = parse_hex_color(hex)?;
+    Some(16 + 36 * nearest(r) + 6 * nearest(g) + nearest(b))
+}
+
+// Nome utilizável como nome de arquivo: o que não for letra, número, "-" ou "_" vira "-"
+fn file_safe_name(name: &str) -> String {
+    name.trim().chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect()
+}
+
+// Cores ANSI só quando a saída é um terminal e NO_COLOR não está definida (https://no-color.org)
+fn ansi_colors_supported() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && io::stdout().is_terminal()
+}
+
+// Gravar um arquivo sem risco de deixá-lo pela metade: escreve em "<path>.tmp" e renomeia
+// por cima do original (operação atômica no mesmo sistema de arquivos)
+fn write_atomically(path: &str, contents: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let temp_path = format!("{}.tmp", path);
+    
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+    
+    if let Err(e) = fs::rename(&temp_path, path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Não foi possível substituir {}: {}", path, e).into());
+    }
+    Ok(())
+}
+
+// Campo CSV conforme a RFC 4180: entre aspas se tiver vírgula, aspas ou quebra de linha
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Ler registros CSV (RFC 4180): campos entre aspas podem conter vírgulas, quebras de linha e "" escapado
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+    
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    
+    records
+}
+
+// Converter uma duração em horas para minutos inteiros
+fn duration_minutes(hours: f32) -> i32 {
+    (hours * 60.0).round() as i32
+}
+
+// Converter minutos desde a meia-noite em "HH:MM"
+fn format_minutes(minutes: i32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+impl WeeklyOrganizer {
+    // Construtor antigo, mantido só por compatibilidade: é um atalho para `open`.
+    // ATENÇÃO: entra em pânico se o arquivo de dados existir mas não puder ser lido
+    // (ex.: JSON corrompido), em vez de começar vazio e sobrescrevê-lo ao salvar como
+    // acontecia antes. Código de biblioteca deve usar `open` e tratar o erro
+    #[deprecated(note = "entra em pânico com arquivo de dados ilegível; use WeeklyOrganizer::open e trate o erro")]
+    pub fn new(data_file: &str) -> Self {
+        Self::open(data_file)
+            .unwrap_or_else(|e| panic!("Não foi possível carregar {}: {}", data_file, e))
+    }
+    
+    // Construtor que devolve o erro de leitura do arquivo de dados (arquivo ausente não é erro)
+    pub fn open(data_file: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::open_with_locale(data_file, Locale::default())
+    }
+    
+    // Como open, escolhendo o idioma dos dias (ex.: Locale::en_us())
+    pub fn open_with_locale(data_file: &str, locale: Locale) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut organizer = WeeklyOrganizer {
+            activities: Vec::new(),
+            categories: HashMap::new(),
+            data_file: data_file.to_string(),
+            stats_precision: 1,
+            category_keywords: Vec::new(),
+            fallback_color: "#6B7280".to_string(),
+            autosave: true,
+            dirty: Cell::new(false),
+            day_index: RefCell::new(None),
+            validators: Vec::new(),
+            quiet_hours: Vec::new(),
+            grid_collision_display: GridCollisionDisplay::default(),
+            waitlist: Vec::new(),
+            goals: HashMap::new(),
+            title_normalization: TitleNormalization::default(),
+            tentative_blocks: true,
+            align_to_slots: true,
+            start_hour: DEFAULT_START_HOUR,
+            end_hour: DEFAULT_END_HOUR,
+            slot_minutes: DEFAULT_SLOT_MINUTES,
+            ansi_colors: ansi_colors_supported(),
+            locale,
+            active_schedule: DEFAULT_SCHEDULE.to_string(),
+            schedules: HashMap::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        };
+        
+        // Categorias padrão
+        organizer.init_default_categories();
+        organizer.init_default_category_keywords();
+        
+        // Carregar dados existentes
+        organizer.load_data()?;
+        
+        Ok(organizer)
+    }
+    
+    // Inicializar categorias padrão
+    fn init_default_categories(&mut self) {
+        let default_categories = [
+            ("trabalho", Category { name: "Trabalho".to_string(), color: "#3B82F6".to_string() }),
+            ("pessoal", Category { name: "Pessoal".to_string(), color: "#10B981".to_string() }),
+            ("saude", Category { name: "Saúde".to_string(), color: "#F59E0B".to_string() }),
+            ("estudo", Category { name: "Estudo".to_string(), color: "#8B5CF6".to_string() }),
+            ("lazer", Category { name: "Lazer".to_string(), color: "#EF4444".to_string() }),
+            ("reuniao", Category { name: "Reunião".to_string(), color: "#F97316".to_string() }),
+            ("exercicio", Category { name: "Exercício".to_string(), color: "#06B6D4".to_string() }),
+        ];
+        
+        for (key, category) in default_categories {
+            self.categories.insert(key.to_string(), category);
+        }
+    }
+    
+    // Inicializar palavras-chave padrão para sugestão de categoria
+    fn init_default_category_keywords(&mut self) {
+        let default_keywords = [
+            ("reunião", "reuniao"), ("reuniao", "reuniao"), ("call", "reuniao"),
+            ("projeto", "trabalho"), ("cliente", "trabalho"), ("relatório", "trabalho"),
+            ("aula", "estudo"), ("curso", "estudo"), ("estudar", "estudo"), ("leitura", "estudo"),
+            ("médico", "saude"), ("consulta", "saude"), ("terapia", "saude"),
+            ("academia", "exercicio"), ("treino", "exercicio"), ("corrida", "exercicio"), ("yoga", "exercicio"),
+            ("cinema", "lazer"), ("jogo", "lazer"), ("passeio", "lazer"),
+            ("mercado", "pessoal"), ("almoço", "pessoal"), ("jantar", "pessoal"),
+        ];
+        
+        self.category_keywords = default_keywords.iter()
+            .map(|(keyword, category)| (keyword.to_string(), category.to_string()))
+            .collect();
+    }
+    
+    // Gerar horários de slot_minutes em slot_minutes (padrão: 30 em 30, das 6h às 22h30)
+    pub fn generate_time_slots(&self) -> Vec<String> {
+        (self.start_hour * 60..self.end_hour * 60)
+            .step_by(self.slot_minutes as usize)
+            .map(format_minutes)
+            .collect()
+    }
+    
+    // Validar horário
+    fn validate_time(&self, time: &str) -> Result<(), WeeklyError> {
+        if NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+            return Err(WeeklyError::InvalidTime(time.to_string()));
+        }
+        Ok(())
+    }
+    
+    // Validar que o horário cai em um slot da grade (múltiplo de slot_minutes), se o alinhamento estiver ativo
+    fn validate_slot(&self, time: &str) -> Result<(), WeeklyError> {
+        if self.align_to_slots {
+            if let Some(minutes) = parse_minutes(time) {
+                if minutes % self.slot_minutes as i32 != 0 {
+                    return Err(WeeklyError::UnalignedTime { time: time.to_string(), slot_minutes: self.slot_minutes });
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    // Validar duração: de um slot a 8 horas, em múltiplos de slot_minutes
+    fn validate_duration(&self, duration: f32) -> Result<(), WeeklyError> {
+        let min = self.slot_minutes as f32 / 60.0;
+        if !(min..=8.0).contains(&duration) {
+            return Err(WeeklyError::DurationOutOfRange { duration, min });
+        }
+        let slots = duration / min;
+        if (slots - slots.round()).abs() > 1e-4 {
+            return Err(WeeklyError::DurationNotMultipleOfSlot { duration, slot_minutes: self.slot_minutes });
+        }
+        Ok(())
+    }
+    
+    // Converter o dia informado (nome no idioma atual ou forma aceita por parse_day)
+    fn resolve_day(&self, day: &str) -> Result<Weekday, WeeklyError> {
+        self.locale.parse_day(day).ok_or_else(|| WeeklyError::InvalidDay(day.to_string()))
+    }
+    
+    // Todas as atividades que se sobreporiam a uma atividade nesse dia/horário/duração
+    pub fn find_conflicts(&self, day: &str, start_time: &str, duration: f32) -> Vec<&Activity> {
+        match self.resolve_day(day) {
+            Ok(day) => self.conflicts_excluding(day, start_time, duration, None),
+            Err(_) => Vec::new(),
+        }
+    }
+    
+    // Listar todas as atividades do dia que se sobrepõem ao intervalo, ignorando `exclude_id`
+    fn conflicts_excluding(&self, day: Weekday, start_time: &str, duration: f32, exclude_id: Option<&str>) -> Vec<&Activity> {
+        let start_minutes = match parse_minutes(start_time) {
+            Some(minutes) => minutes,
+            None => return Vec::new(),
+        };
+        let end_minutes = start_minutes + duration_minutes(duration);
+        
+        self.overlapping_in(&self.activities, day, start_minutes, end_minutes, exclude_id)
+    }
+    
+    // Atividades de `pool` que se sobrepõem a [start_minutes, end_minutes) do dia.
+    // O que passa da meia-noite continua no dia seguinte (Domingo → Segunda): uma atividade
+    // de 23:30 com 1h30 conflita com outra às 00:30 do dia seguinte, e vice-versa
+    fn overlapping_in<'a>(&self, pool: &'a [Activity], day: Weekday, start_minutes: i32, end_minutes: i32, exclude_id: Option<&str>) -> Vec<&'a Activity> {
+        const WEEK: i32 = 7 * 24 * 60;
+        let day_offset = |day: Weekday| day.num_days_from_monday() as i32 * 24 * 60;
+        let base = day_offset(day);
+        let (query_start, query_end) = (base + start_minutes, base + end_minutes);
+        
+        // Na agenda atual, só o dia e os vizinhos (o que vira a meia-noite) podem se sobrepor
+        let candidates: Vec<&'a Activity> = if std::ptr::eq(pool, self.activities.as_slice()) {
+            let mut positions: Vec<usize> = [day.pred(), day, day.succ()].iter()
+                .flat_map(|d| self.day_positions(*d))
+                .collect();
+            positions.sort_unstable();
+            positions.into_iter().map(|index| &pool[index]).collect()
+        } else {
+            pool.iter().collect()
+        };
+        
+        candidates.into_iter()
+            .filter(|activity| Some(activity.id.as_str()) != exclude_id)
+            .filter(|activity| self.blocks_time(activity))
+            .filter(|activity| match (activity.start_minutes(), activity.end_minutes()) {
+                // Verificar sobreposição na semana circular
+                (Some(activity_start), Some(activity_end)) => {
+                    let offset = day_offset(activity.day);
+                    [-WEEK, 0, WEEK].iter().any(|shift| {
+                        query_start < offset + activity_end + shift && query_end > offset + activity_start + shift
+                    })
+                }
+                _ => false,
+            })
+            .collect()
+    }
+    
+    // Posições em `activities` das atividades do dia, pelo índice (refeito se estiver desatualizado)
+    fn day_positions(&self, day: Weekday) -> Vec<usize> {
+        let mut index = self.day_index.borrow_mut();
+        let index = index.get_or_insert_with(|| {
+            let mut by_day: HashMap<Weekday, Vec<usize>> = HashMap::new();
+            for (position, activity) in self.activities.iter().enumerate() {
+                by_day.entry(activity.day).or_default().push(position);
+            }
+            by_day
+        });
+        index.get(&day).cloned().unwrap_or_default()
+    }
+    
+    // Descartar o índice por dia; chamado sempre que `activities` muda de tamanho, ordem ou dias
+    fn invalidate_day_index(&self) {
+        self.day_index.borrow_mut().take();
+    }
+    
+    // Adicionar nova atividade
+    pub fn add_activity(&mut self, title: &str, category: &str, day: &str, start_time: &str, duration: f32, location: Option<String>, description: Option<String>) -> Result<String, WeeklyError> {
+        // Criar atividade
+        let activity = Activity {
+            id: Uuid::new_v4().to_string(),
+            title: title.to_string(),
+            category: category.to_string(),
+            duration,
+            start_time: start_time.to_string(),
+            location,
+            description,
+            day: self.resolve_day(day)?,
+            created_at: Local::now(),
+            notes: Vec::new(),
+            effort: None,
+            depends_on: None,
+            status: ActivityStatus::default(),
+            series_id: None,
+            recurrence: None,
+            priority: Priority::default(),
+            tags: Vec::new(),
+            reminder_minutes: None,
+        };
+        
+        self.insert_activity(activity)
+    }
+    
+    // Adicionar várias atividades de uma vez (tudo ou nada). Cada item é validado contra a
+    // agenda e os itens anteriores do lote; salva uma única vez. Retorna os IDs na ordem dos itens
+    pub fn add_activities(&mut self, activities: Vec<ActivityParams>) -> Result<Vec<String>, WeeklyError> {
+        let mut pool = self.activities.clone();
+        let mut ids = Vec::new();
+        
+        for (index, params) in activities.iter().enumerate() {
+            let mut activity = params.to_activity();
+            activity.title = self.title_normalization.apply(&activity.title);
+            self.validate_activity_in(&activity, &pool)
+                .map_err(|e| WeeklyError::BatchItem { index, title: params.title.clone(), error: Box::new(e) })?;
+            ids.push(activity.id.clone());
+            pool.push(activity);
+        }
+        
+        if ids.is_empty() {
+            return Ok(ids);
+        }
+        
+        self.record_undo();
+        self.activities = pool;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(ids)
+    }
+    
+    // Validar e inserir uma atividade já montada
+    fn insert_activity(&mut self, mut activity: Activity) -> Result<String, WeeklyError> {
+        activity.title = self.title_normalization.apply(&activity.title);
+        self.validate_activity(&activity)?;
+        
+        self.record_undo();
+        let id = activity.id.clone();
+        self.activities.push(activity);
+        
+        // Salvar automaticamente
+        self.persist();
+        
+        Ok(id)
+    }
+    
+    // Validar uma atividade nova ou editada (o conflito ignora a própria atividade)
+    fn validate_activity(&self, activity: &Activity) -> Result<(), WeeklyError> {
+        self.validate_activity_in(activity, &self.activities)
+    }
+    
+    // Validar uma atividade contra um conjunto hipotético de atividades (operações em lote)
+    fn validate_activity_in(&self, activity: &Activity, pool: &[Activity]) -> Result<(), WeeklyError> {
+        self.validate_time(&activity.start_time)?;
+        self.validate_slot(&activity.start_time)?;
+        
+        if !self.categories.contains_key(&activity.category) {
+            return Err(WeeklyError::UnknownCategory(activity.category.clone()));
+        }
+        
+        self.validate_duration(activity.duration)?;
+        
+        if activity.title.trim().is_empty() {
+            return Err(WeeklyError::EmptyTitle);
+        }
+        
+        if let Some(effort) = activity.effort {
+            if !(1..=5).contains(&effort) {
+                return Err(WeeklyError::EffortOutOfRange(effort));
+            }
+        }
+        
+        // Horários de silêncio
+        if let (Some(start), Some(end)) = (activity.start_minutes(), activity.end_minutes()) {
+            if let Some((from, to)) = self.quiet_hours_overlap(start, end) {
+                return Err(WeeklyError::QuietHours { from: from.clone(), to: to.clone() });
+            }
+        }
+        
+        // Verificar conflitos (canceladas, e provisórias se configurado, não ocupam horário)
+        if let (true, Some(start), Some(end)) = (self.blocks_time(activity), activity.start_minutes(), activity.end_minutes()) {
+            let conflicting = self.overlapping_in(pool, activity.day, start, end, Some(&activity.id));
+            if !conflicting.is_empty() {
+                return Err(WeeklyError::TimeConflict { with: conflicting.iter().map(|a| a.title.clone()).collect() });
+            }
+        }
+        
+        // Dependências: a atividade só pode começar depois que a dependência terminar
+        if let Some(dependency_id) = &activity.depends_on {
+            if *dependency_id == activity.id {
+                return Err(WeeklyError::SelfDependency);
+            }
+            let dependency = pool.iter().find(|a| a.id == *dependency_id)
+                .ok_or_else(|| WeeklyError::DependencyNotFound(dependency_id.clone()))?;
+            if Self::dependency_violated(activity, dependency) {
+                return Err(WeeklyError::StartsBeforeDependency { activity: activity.title.clone(), dependency: dependency.title.clone() });
+            }
+        }
+        if let Some(dependent) = pool.iter().find(|a| {
+            a.id != activity.id && a.depends_on.as_deref() == Some(activity.id.as_str()) && Self::dependency_violated(a, activity)
+        }) {
+            return Err(WeeklyError::DependentWouldStartEarlier { dependent: dependent.title.clone() });
+        }
+        
+        // Regras personalizadas
+        for validator in &self.validators {
+            validator(activity).map_err(WeeklyError::Rule)?;
+        }
+        
+        Ok(())
+    }
+    
+    // Registrar uma regra personalizada (ex.: "trabalho só em dias úteis"),
+    // aplicada por add_activity/edit_activity depois das validações padrão
+    pub fn add_validator(&mut self, validator: impl Fn(&Activity) -> Result<(), String> + 'static) {
+        self.validators.push(Box::new(validator));
+    }
+    
+    // Remover todas as regras personalizadas
+    pub fn clear_validators(&mut self) {
+        self.validators.clear();
+    }
+    
+    // Editar atividade
+    pub fn edit_activity(&mut self, id: &str, title: Option<&str>, category: Option<&str>, day: Option<&str>, start_time: Option<&str>, duration: Option<f32>, location: Option<String>, description: Option<String>) -> Result<(), WeeklyError> {
+        let activity = self.get_activity(id)
+            .ok_or(WeeklyError::ActivityNotFound)?;
+        let title = title.map(|t| self.title_normalization.apply(t));
+        let title = title.as_deref();
+        let day = day.map(|d| self.resolve_day(d)).transpose()?;
+        
+        // Criar uma cópia para validação
+        let mut temp_activity = activity.clone();
+        
+        // Aplicar mudanças temporariamente
+        if let Some(t) = title { temp_activity.title = t.to_string(); }
+        if let Some(c) = category { temp_activity.category = c.to_string(); }
+        if let Some(d) = day { temp_activity.day = d; }
+        if let Some(st) = start_time { temp_activity.start_time = st.to_string(); }
+        if let Some(dur) = duration { temp_activity.duration = dur; }
+        
+        // Validações (conflitos excluindo a própria atividade)
+        self.validate_activity(&temp_activity)?;
+        
+        // Aplicar mudanças
+        self.record_undo();
+        let activity = self.get_activity_mut(id).unwrap();
+        if let Some(t) = title { activity.title = t.to_string(); }
+        if let Some(c) = category { activity.category = c.to_string(); }
+        if let Some(d) = day { activity.day = d; }
+        if let Some(st) = start_time { activity.start_time = st.to_string(); }
+        if let Some(dur) = duration { activity.duration = dur; }
+        if let Some(loc) = location { activity.location = Some(loc); }
+        if let Some(desc) = description { activity.description = Some(desc); }
+        
+        // Salvar automaticamente
+        self.persist();
+        
+        Ok(())
+    }
+    
+    // Editar qualquer campo de uma atividade, inclusive esforço, prioridade e tags, com a
+    // mesma validação e o mesmo desfazer de edit_activity
+    pub fn edit_activity_with(&mut self, id: &str, changes: ActivityPatch) -> Result<(), WeeklyError> {
+        let mut updated = self.get_activity(id)
+            .ok_or(WeeklyError::ActivityNotFound)?
+            .clone();
+        changes.apply(&mut updated);
+        if changes.title.is_some() {
+            updated.title = self.title_normalization.apply(&updated.title);
+        }
+        
+        // Validações (conflitos excluindo a própria atividade)
+        self.validate_activity(&updated)?;
+        
+        self.record_undo();
+        *self.get_activity_mut(id).unwrap() = updated;
+        
+        // Salvar automaticamente
+        self.persist();
+        
+        Ok(())
+    }
+    
+    // Remover atividade. Retorna os IDs agendados da lista de espera no horário liberado
+    pub fn remove_activity(&mut self, id: &str) -> Result<Vec<String>, WeeklyError> {
+        if self.get_activity(id).is_none() {
+            return Err(WeeklyError::ActivityNotFound);
+        }
+        self.record_undo();
+        Ok(self.remove_ids(&[id.to_string()]))
+    }
+    
+    // Remover as atividades (sem registrar desfazer) e encaixar a lista de espera no espaço
+    // liberado, retornando os IDs agendados
+    fn remove_ids(&mut self, ids: &[String]) -> Vec<String> {
+        self.activities.retain(|a| !ids.contains(&a.id));
+        
+        // Quem dependia de uma atividade removida fica sem dependência
+        for activity in self.activities.iter_mut() {
+            if activity.depends_on.as_ref().is_some_and(|d| ids.contains(d)) {
+                activity.depends_on = None;
+            }
+        }
+        
+        // Salvar automaticamente
+        self.persist();
+        
+        // Tentar encaixar a lista de espera no horário liberado
+        self.schedule_waitlist()
+    }
+    
+    // Guardar o estado atual antes de uma alteração desfazível (uma nova alteração
+    // descarta o que havia para refazer)
+    fn record_undo(&mut self) {
+        let state = self.history_state();
+        self.push_undo(state);
+    }
+    
+    // Atividades e lista de espera atuais, como ficam guardadas no histórico
+    fn history_state(&self) -> HistoryState {
+        HistoryState { activities: self.activities.clone(), waitlist: self.waitlist.clone() }
+    }
+    
+    // Empilhar um estado capturado antes de uma alteração já feita
+    fn push_undo(&mut self, state: HistoryState) {
+        self.undo_stack.push(state);
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+    
+    // Esquecer desfazer/refazer quando o histórico deixa de valer para o estado atual
+    // (outra agenda, dados recarregados ou categorias que as versões antigas usavam)
+    fn clear_history(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+    
+    // Desfazer a última alteração nas atividades ou na lista de espera
+    pub fn undo(&mut self) -> Result<(), String> {
+        let previous = self.undo_stack.pop().ok_or("Nada para desfazer")?;
+        let current = HistoryState {
+            activities: std::mem::replace(&mut self.activities, previous.activities),
+            waitlist: std::mem::replace(&mut self.waitlist, previous.waitlist),
+        };
+        self.redo_stack.push(current);
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Refazer a última alteração desfeita por undo
+    pub fn redo(&mut self) -> Result<(), String> {
+        let next = self.redo_stack.pop().ok_or("Nada para refazer")?;
+        let current = HistoryState {
+            activities: std::mem::replace(&mut self.activities, next.activities),
+            waitlist: std::mem::replace(&mut self.waitlist, next.waitlist),
+        };
+        self.undo_stack.push(current);
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Listar atividades de um dia
+    pub fn get_activities_by_day(&self, day: &str) -> Vec<&Activity> {
+        let day = match self.resolve_day(day) {
+            Ok(day) => day,
+            Err(_) => return Vec::new(),
+        };
+        let mut activities: Vec<&Activity> = self.day_positions(day).into_iter()
+            .map(|index| &self.activities[index])
+            .filter(|a| a.status != ActivityStatus::Cancelled)
+            .collect();
+        
+        // Ordenar por horário
+        activities.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+        activities
+    }
+    
+    // Obter todas as atividades ordenadas
+    pub fn get_all_activities(&self) -> Vec<&Activity> {
+        self.get_all_activities_sorted(ActivityOrder::DayAndTime)
+    }
+    
+    // Obter todas as atividades na ordem pedida (por dia e horário, ou por prioridade)
+    pub fn get_all_activities_sorted(&self, order: ActivityOrder) -> Vec<&Activity> {
+        let mut activities: Vec<&Activity> = self.live_activities()
+            .collect();
+        activities.sort_by(|a, b| {
+            let by_priority = match order {
+                ActivityOrder::DayAndTime => std::cmp::Ordering::Equal,
+                ActivityOrder::PriorityThenTime => b.priority.cmp(&a.priority),
+            };
+            by_priority
+                .then_with(|| a.day.num_days_from_monday().cmp(&b.day.num_days_from_monday()))
+                .then_with(|| a.start_time.cmp(&b.start_time))
+        });
+        activities
+    }
+    
+    // Atividades (não canceladas) com a tag informada, por dia e horário
+    pub fn get_activities_by_tag(&self, tag: &str) -> Vec<&Activity> {
+        let tag = tag.trim().to_lowercase();
+        self.get_all_activities().into_iter()
+            .filter(|a| a.tags.contains(&tag))
+            .collect()
+    }
+    
+    // Todas as tags em uso, em ordem alfabética
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.activities.iter()
+            .flat_map(|a| a.tags.iter().cloned())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+    
+    // Atividades (não canceladas) com a prioridade informada, por dia e horário
+    pub fn get_activities_by_priority(&self, priority: Priority) -> Vec<&Activity> {
+        self.get_all_activities().into_iter()
+            .filter(|a| a.priority == priority)
+            .collect()
+    }
+    
+    // Calcular estatísticas semanais
+    pub fn calculate_weekly_stats(&self) -> WeeklyStats {
+        // Somar em minutos inteiros e só converter para horas no fim (sem erro acumulado de f32)
+        let minutes = self.calculate_weekly_stats_minutes();
+        let to_hours = |totals: HashMap<String, u32>| -> HashMap<String, f32> {
+            totals.into_iter().map(|(key, m)| (key, m as f32 / 60.0)).collect()
+        };
+        
+        // Dias mais e menos ocupados (empates ficam com o primeiro da semana)
+        let day_minutes = |day: &str| minutes.by_day.get(day).copied().unwrap_or(0);
+        let (busiest_day, quietest_day) = if minutes.activity_count == 0 {
+            (None, None)
+        } else {
+            let busiest = WEEK_DAYS.iter().rev().max_by_key(|d| day_minutes(d)).map(|d| d.to_string());
+            let quietest = WEEK_DAYS.iter().min_by_key(|d| day_minutes(d)).map(|d| d.to_string());
+            (busiest, quietest)
+        };
+        
+        let total_time = minutes.total_minutes as f32 / 60.0;
+        let available = self.available_hours();
+        
+        let mut stats = WeeklyStats {
+            total_time,
+            by_category: to_hours(minutes.by_category),
+            by_day: to_hours(minutes.by_day),
+            activity_count: minutes.activity_count,
+            effort_by_day: HashMap::new(),
+            free_time: (available - total_time).max(0.0),
+            utilization_percent: if available > 0.0 { total_time / available * 100.0 } else { 0.0 },
+            busiest_day,
+            quietest_day,
+        };
+        
+        for activity in self.live_activities() {
+            // Esforço por dia
+            if let Some(effort) = activity.effort {
+                *stats.effort_by_day.entry(day_name(activity.day).to_string()).or_insert(0) += effort as u32;
+            }
+        }
+        
+        stats
+    }
+    
+    // Calcular estatísticas semanais em minutos inteiros (totais exatos)
+    pub fn calculate_weekly_stats_minutes(&self) -> WeeklyStatsMinutes {
+        let mut stats = WeeklyStatsMinutes {
+            total_minutes: 0,
+            by_category: HashMap::new(),
+            by_day: HashMap::new(),
+            activity_count: 0,
+        };
+        
+        for activity in self.live_activities() {
+            stats.activity_count += 1;
+            let minutes = duration_minutes(activity.duration).max(0) as u32;
+            
+            // Tempo total
+            stats.total_minutes += minutes;
+            
+            // Por categoria
+            *stats.by_category.entry(activity.category.clone()).or_insert(0) += minutes;
+            
+            // Por dia
+            *stats.by_day.entry(day_name(activity.day).to_string()).or_insert(0) += minutes;
+        }
+        
+        stats
+    }
+    
+    // Formatar tempo ("15min", "1h", "1h 30min"). Arredonda para minutos inteiros antes
+    // de decidir o formato, então somas imprecisas de f32 viram "1h" e não "60min"
+    pub fn format_time(hours: f32) -> String {
+        let total = duration_minutes(hours);
+        match (total / 60, total % 60) {
+            (0, minutes) => format!("{}min", minutes),
+            (whole_hours, 0) => format!("{}h", whole_hours),
+            (whole_hours, minutes) => format!("{}h {}min", whole_hours, minutes),
+        }
+    }
+    
+    // Slot coberto por uma atividade que começou antes (no mesmo dia ou, passando da
+    // meia-noite, no dia anterior): recebe a continuação "│" em vez de ficar vazio
+    fn slot_continues(&self, day: Weekday, minutes: i32) -> bool {
+        let previous_day = day.pred();
+        self.live_activities()
+            .any(|a| match (a.start_minutes(), a.end_minutes()) {
+                (Some(start), Some(end)) if a.day == day => start < minutes && end > minutes,
+                (Some(_), Some(end)) if a.day == previous_day => end - 24 * 60 > minutes,
+                _ => false,
+            })
+    }
+    
+    // Conteúdo da grade semanal, de Segunda a Domingo e ainda sem alinhamento
+    fn grid_rows(&self) -> Vec<GridRow<'_>> {
+        // Todas as atividades por (dia, horário): mais de uma é um choque que a grade não pode esconder
+        let mut slots: HashMap<(Weekday, &str), Vec<&Activity>> = HashMap::new();
+        for activity in self.live_activities() {
+            slots.entry((activity.day, activity.start_time.as_str())).or_default().push(activity);
+        }
+        
+        self.generate_time_slots().into_iter()
+            .map(|time| {
+                let minutes = parse_minutes(&time).unwrap();
+                let cells = WEEKDAYS.iter()
+                    .map(|day| {
+                        let activities = slots.get(&(*day, time.as_str())).map_or(&[][..], |v| v.as_slice());
+                        match activities {
+                            [] if self.slot_continues(*day, minutes) => ("│".to_string(), None),
+                            [] => (String::new(), None),
+                            [act] => {
+                                // Provisórias ganham o prefixo "~" (e um caractere a menos de título)
+                                let (marker, width) = if act.status == ActivityStatus::Tentative { ("~", 11) } else { ("", 12) };
+                                // Contar caracteres, não bytes: fatiar por byte quebra em acentos ("Reunião")
+                                let short_title = if act.title.chars().count() > width {
+                                    format!("{}...", act.title.chars().take(width - 3).collect::<String>())
+                                } else {
+                                    act.title.clone()
+                                };
+                                (format!("{}{}", marker, short_title), Some(act.category.as_str()))
+                            },
+                            [first, ..] => {
+                                let indicator = format!("⚠{}", activities.len());
+                                let cell = match self.grid_collision_display {
+                                    GridCollisionDisplay::Count => indicator,
+                                    GridCollisionDisplay::CountAndTitle => {
+                                        let title: String = first.title.chars().take(12 - indicator.chars().count() - 1).collect();
+                                        format!("{} {}", indicator, title)
+                                    }
+                                };
+                                (cell, None)
+                            },
+                        }
+                    })
+                    .collect();
+                (time, cells)
+            })
+            .collect()
+    }
+    
+    // Exibir grade semanal
+    pub fn display_weekly_grid(&self) {
+        let rows = self.grid_rows();
+        
+        println!("\n╔═══════════════════════════════════════════════════════════════════════════════════════════════════════════════════╗");
+        println!("║                                              ORGANIZADOR SEMANAL                                                     ║");
+        println!("╠═══════════╦══════════════╦══════════════╦══════════════╦══════════════╦══════════════╦══════════════╦══════════════╣");
+        print!("║   HORÁRIO ║");
+        for day in &WEEKDAYS {
+            print!(" {:^12} ║", self.locale.day_name(*day));
+        }
+        println!();
+        println!("╠═══════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╣");
+        
+        for (i, (time, cells)) in rows.iter().enumerate() {
+            // Mostrar apenas horários completos
+            let minutes = parse_minutes(time).unwrap();
+            if minutes % 60 == 0 {
+                print!("║ {:^9} ║", time);
+            } else {
+                print!("║ {:^9} ║", "");
+            }
+            
+            for (cell, category) in cells {
+                // Alinhar antes de colorir: os códigos ANSI não ocupam espaço na tela
+                let cell = format!("{:^12}", cell);
+                match category {
+                    Some(category) => print!(" {} ║", self.colorize(&cell, category)),
+                    None => print!(" {} ║", cell),
+                }
+            }
+            println!();
+            
+            // Linha separadora a cada hora (depois do último slot dela)
+            if (minutes + self.slot_minutes as i32) % 60 == 0 {
+                if i < rows.len() - 1 {
+                    println!("╠═══════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╣");
+                }
+            }
+        }
+        
+        println!("╚═══════════╩══════════════╩══════════════╩══════════════╩══════════════╩══════════════╩══════════════╩══════════════╝");
+    }
+    
+    // Exibir estatísticas
+    pub fn display_stats(&self) {
+        let stats = self.calculate_weekly_stats();
+        
+        println!("\n╔══════════════════════════════════════════════════════════════════╗");
+        println!("║                      ESTATÍSTICAS SEMANAIS                      ║");
+        println!("╠══════════════════════════════════════════════════════════════════╣");
+        println!("║ Total de atividades: {:^42} ║", stats.activity_count);
+        println!("║ Tempo total semanal: {:^42} ║", Self::format_time(stats.total_time));
+        println!("║ Tempo livre:         {:^42} ║", Self::format_time(stats.free_time));
+        println!("║ Semana planejada:    {:^42} ║", format!("{:.*}%", self.stats_precision, stats.utilization_percent));
+        if let (Some(busiest), Some(quietest)) = (&stats.busiest_day, &stats.quietest_day) {
+            // As estatísticas guardam o nome canônico; exibir no idioma atual
+            let localized = |day: &String| parse_day(day).map_or_else(|| day.clone(), |d| self.locale.day_name(d).to_string());
+            println!("║ Dia mais cheio:      {:^42} ║", localized(busiest));
+            println!("║ Dia mais tranquilo:  {:^42} ║", localized(quietest));
+        }
+        println!("║ Diversidade:         {:^42} ║", format!("{:.2}", self.category_diversity()));
+        println!("╠══════════════════════════════════════════════════════════════════╣");
+        println!("║                      POR CATEGORIA                              ║");
+        println!("╠══════════════════════════════════════════════════════════════════╣");
+        
+        let precision = self.stats_precision;
+        for (category_key, percentage) in self.category_percentages(&stats) {
+            if let Some(category) = self.categories.get(&category_key) {
+                println!("║ {:20} │ {:>12} │ {:>6.*}% ║", 
+                    category.name, 
+                    Self::format_time(stats.by_category[&category_key]),
+                    precision,
+                    percentage
+                );
+            }
+        }
+        
+        println!("╠══════════════════════════════════════════════════════════════════╣");
+        println!("║                        POR DIA                                  ║");
+        println!("╠══════════════════════════════════════════════════════════════════╣");
+        
+        let week_span = format!("{} → {}:", self.locale.short_day_name(Weekday::Mon), self.locale.short_day_name(Weekday::Sun));
+        println!("║ {:10} {:<53} ║", week_span, Self::sparkline(&stats));
+        
+        let day_times: Vec<f32> = WEEKDAYS.iter().map(|day| *stats.by_day.get(day_name(*day)).unwrap_or(&0.0)).collect();
+        let day_percentages = apportion_percentages(&day_times, precision);
+        for ((day, day_time), percentage) in WEEKDAYS.iter().zip(&day_times).zip(day_percentages) {
+            println!("║ {:20} │ {:>12} │ {:>6.*}% ║", 
+                self.locale.day_name(*day), 
+                Self::format_time(*day_time),
+                precision,
+                percentage
+            );
+        }
+        
+        let mut location_stats: Vec<_> = self.time_by_location().into_iter().collect();
+        if !location_stats.is_empty() {
+            location_stats.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then_with(|| a.0.cmp(&b.0)));
+            
+            println!("╠══════════════════════════════════════════════════════════════════╣");
+            println!("║                        POR LOCAL                                ║");
+            println!("╠══════════════════════════════════════════════════════════════════╣");
+            for (location, time) in location_stats {
+                println!("║ {:20} │ {:>12} │ {:>27} ║", location, Self::format_time(time), "");
+            }
+        }
+        
+        let goals = self.goal_progress();
+        if !goals.is_empty() {
+            println!("╠══════════════════════════════════════════════════════════════════╣");
+            println!("║                        METAS                                    ║");
+            println!("╠══════════════════════════════════════════════════════════════════╣");
+            for goal in goals {
+                let name = self.categories.get(&goal.category).map_or(&goal.category, |c| &c.name);
+                let filled = ((goal.percentage.min(100.0) / 100.0) * 20.0).round() as usize;
+                println!("║ {:20} │ {}{} │ {:>5.0}% de {:<10} ║",
+                    name,
+                    "█".repeat(filled),
+                    "░".repeat(20 - filled),
+                    goal.percentage,
+                    Self::format_time(goal.target_hours)
+                );
+            }
+        }
+        
+        // Alertar sobre dias de esforço alto mesmo com poucas horas
+        for day in WEEKDAYS {
+            let effort = stats.effort_by_day.get(day_name(day)).copied().unwrap_or(0);
+            let day_time = stats.by_day.get(day_name(day)).copied().unwrap_or(0.0);
+            if effort >= HIGH_DAILY_EFFORT && day_time <= 8.0 {
+                println!("║ ⚠️  {:10} esforço alto ({}) em apenas {:<24} ║", self.locale.day_name(day), effort, Self::format_time(day_time));
+            }
+        }
+        
+        println!("╚══════════════════════════════════════════════════════════════════╝");
+    }
+    
+    // Minigráfico das horas por dia (segunda a domingo), relativo ao dia mais cheio
+    pub fn sparkline(stats: &WeeklyStats) -> String {
+        const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let hours: Vec<f32> = WEEK_DAYS.iter()
+            .map(|day| stats.by_day.get(*day).copied().unwrap_or(0.0))
+            .collect();
+        let busiest = hours.iter().copied().fold(0.0, f32::max);
+        
+        hours.iter()
+            .map(|h| {
+                if busiest <= 0.0 {
+                    BLOCKS[0]
+                } else {
+                    BLOCKS[((h / busiest) * (BLOCKS.len() - 1) as f32).round() as usize]
+                }
+            })
+            .collect()
+    }
+    
+    // Salvar dados em arquivo JSON
+    pub fn save_data(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_data_file_path()?;
+        
+        let data = self.to_save_data();
+        let json = serde_json::to_string_pretty(&data)?;
+        write_atomically(&self.data_file, &json)?;
+        self.dirty.set(false);
+        Ok(())
+    }
+    
+    // Registrar uma alteração e salvar, se o salvamento automático estiver ligado
+    fn persist(&self) {
+        self.invalidate_day_index();
+        self.dirty.set(true);
+        if !self.autosave {
+            return;
+        }
+        if let Err(e) = self.save_data() {
+            println!("Aviso: Erro ao salvar dados: {}", e);
+        }
+    }
+    
+    // Ligar/desligar o salvamento a cada alteração (padrão: ligado). Ligado, cada alteração
+    // regrava o arquivo inteiro: seguro, mas lento em lotes grandes. Desligado, as alterações
+    // ficam só na memória até flush/save_data e se perdem se o programa cair antes disso
+    pub fn set_autosave(&mut self, enabled: bool) {
+        self.autosave = enabled;
+    }
+    
+    // Indica se cada alteração é gravada imediatamente
+    pub fn autosave(&self) -> bool {
+        self.autosave
+    }
+    
+    // Gravar as alterações pendentes (útil com o autosave desligado); não faz nada se não houver
+    pub fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.dirty.get() {
+            return Ok(());
+        }
+        self.save_data()
+    }
+    
+    // Indica se há alterações que ainda não foram gravadas no arquivo
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.dirty.get()
+    }
+    
+    // Descartar alterações não salvas, voltando ao que open montaria agora: os padrões mais
+    // o conteúdo do arquivo de dados, se existir. Configurações de execução (regras, idioma,
+    // autosave, cores) são mantidas. Se o arquivo não puder ser lido, nada muda
+    pub fn discard_changes(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let fresh = Self::open_with_locale(&self.data_file, self.locale.clone())?;
+        
+        self.activities = fresh.activities;
+        self.categories = fresh.categories;
+        self.category_keywords = fresh.category_keywords;
+        self.quiet_hours = fresh.quiet_hours;
+        self.waitlist = fresh.waitlist;
+        self.goals = fresh.goals;
+        (self.start_hour, self.end_hour) = (fresh.start_hour, fresh.end_hour);
+        self.slot_minutes = fresh.slot_minutes;
+        self.active_schedule = fresh.active_schedule;
+        self.schedules = fresh.schedules;
+        
+        self.invalidate_day_index();
+        self.clear_history();
+        self.dirty.set(false);
+        Ok(())
+    }
+    
+    // Recusar um caminho de dados que aponta para um diretório
+    fn check_data_file_path(&self) -> Result<(), String> {
+        if Path::new(&self.data_file).is_dir() {
+            return Err(format!("O caminho do arquivo de dados é um diretório, não um arquivo: {}", self.data_file));
+        }
+        Ok(())
+    }
+    
+    // Montar a estrutura persistida a partir do estado atual
+    fn to_save_data(&self) -> SaveData {
+        SaveData {
+            version: SAVE_VERSION,
+            activities: self.activities.clone(),
+            categories: self.categories.clone(),
+            category_keywords: Some(self.category_keywords.clone()),
+            quiet_hours: self.quiet_hours.clone(),
+            waitlist: self.waitlist.clone(),
+            goals: self.goals.clone(),
+            working_hours: Some((self.start_hour, self.end_hour)),
+            slot_minutes: Some(self.slot_minutes),
+            active_schedule: Some(self.active_schedule.clone()),
+            schedules: self.schedules.clone(),
+        }
+    }
+    
+    // Carregar dados do arquivo JSON (sem arquivo, não há nada a carregar; um arquivo
+    // ilegível é erro, para que quem chamou não siga em frente e o sobrescreva ao salvar)
+    pub fn load_data(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_data_file_path()?;
+        
+        if !Path::new(&self.data_file).exists() {
+            return Ok(());
+        }
+        
+        let content = fs::read_to_string(&self.data_file)?;
+        let data = parse_save_data(&content)
+            .map_err(|e| format!("Não foi possível ler {}: {}. Corrija ou remova o arquivo", self.data_file, e))?;
+        
+        self.activities = data.activities;
+        self.invalidate_day_index();
+        self.clear_history();
+        // O arquivo traz a lista completa (inclusive as padrão), então categorias removidas continuam removidas
+        if !data.categories.is_empty() {
+            self.categories = data.categories;
+        }
+        if let Some(keywords) = data.category_keywords {
+            self.category_keywords = keywords;
+        }
+        self.quiet_hours = data.quiet_hours;
+        self.waitlist = data.waitlist;
+        self.goals = data.goals;
+        if let Some((start_hour, end_hour)) = data.working_hours {
+            self.start_hour = start_hour;
+            self.end_hour = end_hour;
+        }
+        if let Some(slot_minutes) = data.slot_minutes {
+            self.slot_minutes = slot_minutes;
+        }
+        self.active_schedule = data.active_schedule.unwrap_or_else(|| DEFAULT_SCHEDULE.to_string());
+        self.schedules = data.schedules;
+        
+        Ok(())
+    }
+    
+    // Exportar para CSV
+    pub fn export_to_csv(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.export_to_csv_filtered(filename, |_| true)
+    }
+    
+    // Exportar para CSV apenas as atividades que satisfazem o filtro (o Markdown tem o
+    // equivalente em export_to_markdown_filtered; não há exportação iCal)
+    pub fn export_to_csv_filtered(&self, filename: &str, predicate: impl Fn(&Activity) -> bool) -> Result<(), Box<dyn std::error::Error>> {
+        let mut content = String::new();
+        content.push_str("ID,Título,Categoria,Dia,Horário,Duração(h),Local,Descrição,Criado em\r\n");
+        
+        for activity in self.activities.iter().filter(|a| predicate(a)) {
+            content.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{}\r\n",
+                csv_field(&activity.id),
+                csv_field(&activity.title),
+                csv_field(&activity.category),
+                csv_field(day_name(activity.day)),
+                csv_field(&activity.start_time),
+                activity.duration,
+                csv_field(activity.location.as_deref().unwrap_or("")),
+                csv_field(activity.description.as_deref().unwrap_or("")),
+                activity.created_at.format("%Y-%m-%d %H:%M:%S")
+            ));
+        }
+        
+        fs::write(filename, content)?;
+        Ok(())
+    }
+    
+    // Buscar atividades
+    pub fn search_activities(&self, query: &str) -> Vec<&Activity> {
+        self.search_in(query, SearchFields::ALL)
+    }
+    
+    // Buscar (sem diferenciar maiúsculas) apenas nos campos escolhidos,
+    // ex.: SearchFields::TITLE | SearchFields::LOCATION
+    pub fn search_in(&self, query: &str, fields: SearchFields) -> Vec<&Activity> {
+        let query_lower = query.to_lowercase();
+        self.live_activities()
+            .filter(|activity| {
+                Self::search_fields(activity, fields).iter()
+                    .any(|text| text.to_lowercase().contains(&query_lower))
+            })
+            .collect()
+    }
+    
+    // Buscar com expressão regular em todos os campos (use "(?i)" para ignorar maiúsculas)
+    pub fn search_activities_regex(&self, pattern: &str) -> Result<Vec<&Activity>, regex::Error> {
+        let regex = regex::Regex::new(pattern)?;
+        Ok(self.live_activities()
+            .filter(|activity| {
+                Self::search_fields(activity, SearchFields::ALL).iter()
+                    .any(|text| regex.is_match(text))
+            })
+            .collect())
+    }
+    
+    // Textos da atividade considerados pela busca nos campos escolhidos
+    fn search_fields(activity: &Activity, fields: SearchFields) -> Vec<&str> {
+        let mut texts = Vec::new();
+        if fields.contains(SearchFields::TITLE) {
+            texts.push(activity.title.as_str());
+        }
+        if fields.contains(SearchFields::CATEGORY) {
+            texts.push(activity.category.as_str());
+        }
+        if fields.contains(SearchFields::LOCATION) {
+            texts.extend(activity.location.as_deref());
+        }
+        if fields.contains(SearchFields::DESCRIPTION) {
+            texts.extend(activity.description.as_deref());
+        }
+        if fields.contains(SearchFields::TAGS) {
+            texts.extend(activity.tags.iter().map(String::as_str));
+        }
+        texts
+    }
+    
+    // Alinhar ao múltiplo de slot_minutes mais próximo os horários fora da grade (dados legados).
+    // Vale para o dia inteiro: horários já alinhados fora do expediente não são tocados
+    pub fn snap_to_slots(&mut self) -> SnapReport {
+        let slot = self.slot_minutes as i32;
+        
+        let before = self.history_state();
+        let mut report = SnapReport::default();
+        for activity in &mut self.activities {
+            let current = match activity.start_minutes() {
+                Some(minutes) => minutes,
+                None => continue,
+            };
+            // Empate fica no slot anterior, e nunca passa para 24:00 (outro dia)
+            let down = current - current % slot;
+            let up = down + slot;
+            let nearest = if current - down <= up - current || up >= 24 * 60 { down } else { up };
+            
+            if nearest != current {
+                activity.start_time = format_minutes(nearest);
+                report.adjusted.push(activity.id.clone());
+            }
+        }
+        
+        // Revalidar conflitos das atividades ajustadas (cada par uma vez só)
+        for id in &report.adjusted {
+            let activity = self.activities.iter().find(|a| &a.id == id).unwrap();
+            let (start, end) = (activity.start_minutes().unwrap(), activity.end_minutes().unwrap());
+            
+            for other in self.overlapping_in(&self.activities, activity.day, start, end, Some(id)) {
+                if !report.conflicts.contains(&(other.id.clone(), id.clone())) {
+                    report.conflicts.push((id.clone(), other.id.clone()));
+                }
+            }
+        }
+        
+        if !report.adjusted.is_empty() {
+            self.invalidate_day_index();
+            self.push_undo(before);
+            self.persist();
+        }
+        
+        report
+    }
+    
+    // Mapa dia → horário de início → atividade (para front-ends indexados por dia e hora)
+    pub fn schedule_matrix(&self) -> HashMap<String, HashMap<String, &Activity>> {
+        let mut matrix: HashMap<String, HashMap<String, &Activity>> = HashMap::new();
+        for activity in self.live_activities() {
+            matrix.entry(day_name(activity.day).to_string())
+                .or_default()
+                .entry(activity.start_time.clone())
+                .or_insert(activity);
+        }
+        matrix
+    }
+    
+    // Sugerir pausas após sequências de atividades que excedem o limite de trabalho contínuo
+    pub fn suggest_breaks(&self, day: &str, work_limit_minutes: u32, break_minutes: u32) -> Vec<String> {
+        // Agrupar atividades encostadas/sobrepostas em blocos contínuos
+        let mut chains: Vec<(i32, i32)> = Vec::new();
+        for activity in self.get_activities_by_day(day) {
+            let (start, end) = match (activity.start_minutes(), activity.end_minutes()) {
+                (Some(start), Some(end)) => (start, end),
+                _ => continue,
+            };
+            
+            match chains.last_mut() {
+                Some(chain) if start <= chain.1 => chain.1 = chain.1.max(end),
+                _ => chains.push((start, end)),
+            }
+        }
+        
+        let mut suggestions = Vec::new();
+        for (i, &(start, end)) in chains.iter().enumerate() {
+            if end - start <= work_limit_minutes as i32 {
+                continue;
+            }
+            
+            let next_start = chains.get(i + 1).map_or(self.end_hour * 60, |next| next.0);
+            if next_start - end >= break_minutes as i32 {
+                suggestions.push(format_minutes(end));
+            } else {
+                suggestions.push(format!("{} (sem intervalo disponível)", format_minutes(end)));
+            }
+        }
+        suggestions
+    }
+    
+    // Definir a quantidade de casas decimais das porcentagens nas estatísticas
+    pub fn set_stats_precision(&mut self, decimals: usize) {
+        self.stats_precision = decimals;
+    }
+    
+    // Porcentagem de cada categoria, arredondada e ajustada para somar 100%
+    pub fn category_percentages(&self, stats: &WeeklyStats) -> Vec<(String, f32)> {
+        let mut category_stats: Vec<_> = stats.by_category.iter().collect();
+        category_stats.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap().then_with(|| a.0.cmp(b.0)));
+        
+        let times: Vec<f32> = category_stats.iter().map(|(_, time)| **time).collect();
+        let percentages = apportion_percentages(&times, self.stats_precision);
+        
+        category_stats.into_iter()
+            .zip(percentages)
+            .map(|((key, _), percentage)| (key.clone(), percentage))
+            .collect()
+    }
+    
+    // Estatísticas semanais com nome, cor e porcentagem de cada categoria (ordem de display_stats)
+    pub fn stats_report(&self) -> StatsReport {
+        let stats = self.calculate_weekly_stats();
+        let categories = self.category_percentages(&stats).into_iter()
+            .map(|(key, percentage)| CategoryStats {
+                name: self.categories.get(&key).map_or_else(|| key.clone(), |c| c.name.clone()),
+                color: self.category_color(&key).to_string(),
+                hours: stats.by_category[&key],
+                percentage,
+                key,
+            })
+            .collect();
+        
+        StatsReport { stats, categories }
+    }
+    
+    // Exportar as estatísticas semanais em JSON (stats_report)
+    pub fn export_stats_json(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string_pretty(&self.stats_report())?;
+        fs::write(filename, json)?;
+        Ok(())
+    }
+    
+    // Adicionar anotação com data/hora a uma atividade
+    pub fn add_note(&mut self, activity_id: &str, text: &str) -> Result<(), String> {
+        if text.trim().is_empty() {
+            return Err("Anotação não pode estar vazia".to_string());
+        }
+        
+        if self.get_activity(activity_id).is_none() {
+            return Err("Atividade não encontrada".to_string());
+        }
+        
+        self.record_undo();
+        let activity = self.get_activity_mut(activity_id).unwrap();
+        activity.notes.push(Note {
+            text: text.trim().to_string(),
+            created_at: Local::now(),
+        });
+        
+        // Salvar automaticamente
+        self.persist();
+        
+        Ok(())
+    }
+    
+    // Pré-visualizar com quais atividades uma edição de dia/horário/duração conflitaria
+    pub fn conflicts_if_edited(&self, id: &str, new_day: Option<&str>, new_start_time: Option<&str>, new_duration: Option<f32>) -> Vec<&Activity> {
+        let activity = match self.get_activity(id) {
+            Some(activity) => activity,
+            None => return Vec::new(),
+        };
+        
+        let day = match new_day.map(|d| self.resolve_day(d)) {
+            Some(Ok(day)) => day,
+            Some(Err(_)) => return Vec::new(),
+            None => activity.day,
+        };
+        self.conflicts_excluding(
+            day,
+            new_start_time.unwrap_or(&activity.start_time),
+            new_duration.unwrap_or(activity.duration),
+            Some(id),
+        )
+    }
+    
+    // Durações distintas usadas nas atividades, em ordem crescente
+    pub fn durations_in_use(&self) -> Vec<f32> {
+        self.duration_counts().into_iter().map(|(duration, _)| duration).collect()
+    }
+    
+    // Quantidade de atividades por duração, em ordem crescente de duração
+    pub fn duration_counts(&self) -> Vec<(f32, usize)> {
+        let mut counts: Vec<(f32, usize)> = Vec::new();
+        for activity in self.live_activities() {
+            match counts.iter_mut().find(|(duration, _)| *duration == activity.duration) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((activity.duration, 1)),
+            }
+        }
+        
+        counts.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        counts
+    }
+    
+    // Sugerir a categoria de um título pelas palavras-chave configuradas (a mais longa vence)
+    pub fn guess_category(&self, title: &str) -> Option<String> {
+        let title_lower = title.to_lowercase();
+        self.category_keywords.iter()
+            .filter(|(keyword, category)| title_lower.contains(keyword.as_str()) && self.categories.contains_key(category))
+            .max_by_key(|(keyword, _)| keyword.chars().count())
+            .map(|(_, category)| category.clone())
+    }
+    
+    // Adicionar (ou substituir) uma regra palavra-chave → categoria
+    pub fn add_category_keyword(&mut self, keyword: &str, category: &str) -> Result<(), String> {
+        let keyword = keyword.trim().to_lowercase();
+        if keyword.is_empty() {
+            return Err("Palavra-chave não pode estar vazia".to_string());
+        }
+        if !self.categories.contains_key(category) {
+            return Err(format!("Categoria '{}' não existe", category));
+        }
+        
+        self.category_keywords.retain(|(k, _)| *k != keyword);
+        self.category_keywords.push((keyword, category.to_string()));
+        
+        // Salvar automaticamente
+        self.persist();
+        
+        Ok(())
+    }
+    
+    // Remover uma regra de palavra-chave
+    pub fn remove_category_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        let keyword = keyword.trim().to_lowercase();
+        let initial_len = self.category_keywords.len();
+        self.category_keywords.retain(|(k, _)| *k != keyword);
+        
+        if self.category_keywords.len() == initial_len {
+            return Err(format!("Palavra-chave '{}' não encontrada", keyword));
+        }
+        
+        // Salvar automaticamente
+        self.persist();
+        
+        Ok(())
+    }
+    
+    // Listar as regras de palavra-chave configuradas
+    pub fn category_keywords(&self) -> &[(String, String)] {
+        &self.category_keywords
+    }
+    
+    // Diretório de histórico, ao lado do arquivo de dados
+    fn archive_dir(&self) -> PathBuf {
+        Path::new(&self.data_file)
+            .parent()
+            .map_or_else(|| PathBuf::from("archive"), |dir| dir.join("archive"))
+    }
+    
+    // Arquivar a semana atual em archive/<timestamp>.json sem limpar a agenda
+    pub fn archive_week(&self, label: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+        let dir = self.archive_dir();
+        fs::create_dir_all(&dir)?;
+        
+        let mut file_name = Local::now().format("%Y%m%d-%H%M%S").to_string();
+        if let Some(label) = label {
+            let label = file_safe_name(label);
+            if !label.is_empty() {
+                file_name.push('-');
+                file_name.push_str(&label);
+            }
+        }
+        
+        let path = dir.join(format!("{}.json", file_name));
+        let json = serde_json::to_string_pretty(&self.to_save_data())?;
+        fs::write(&path, json)?;
+        
+        Ok(path.to_string_lossy().to_string())
+    }
+    
+    // Listar os arquivos de histórico, do mais antigo ao mais recente
+    pub fn list_archives(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let dir = self.archive_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let mut archives = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                archives.push(path.to_string_lossy().to_string());
+            }
+        }
+        archives.sort();
+        Ok(archives)
+    }
+    
+    // Ler as atividades de um arquivo de histórico (a agenda atual não é alterada)
+    pub fn load_archive(&self, path: &str) -> Result<Vec<Activity>, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let data = parse_save_data(&content)?;
+        Ok(data.activities)
+    }
+    
+    // Arquivo de um modelo de semana: templates/<nome>.json, ao lado do arquivo de dados
+    fn template_path(&self, name: &str) -> PathBuf {
+        Path::new(&self.data_file)
+            .parent()
+            .map_or_else(|| PathBuf::from("templates"), |dir| dir.join("templates"))
+            .join(format!("{}.json", file_safe_name(name)))
+    }
+    
+    // Guardar a semana atual como modelo reutilizável (sobrescreve um modelo de mesmo nome).
+    // Canceladas ficam de fora; IDs e datas de criação são gerados de novo ao aplicar
+    pub fn save_as_template(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if file_safe_name(name).is_empty() {
+            return Err("Nome do modelo não pode estar vazio".into());
+        }
+        
+        let template = TemplateFile {
+            activities: self.get_all_activities().into_iter()
+                .map(TemplateActivity::from_activity)
+                .collect(),
+        };
+        let path = self.template_path(name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(&template)?)?;
+        Ok(())
+    }
+    
+    // Aplicar um modelo na agenda ativa, somando às atividades atuais (Append) ou
+    // substituindo-as (Replace). É tudo ou nada: se alguma atividade do modelo não passar
+    // na validação (ex.: conflito), nada muda e o erro lista todas as que falharam
+    pub fn apply_template(&mut self, name: &str, mode: ImportMode) -> Result<(), WeeklyError> {
+        let path = self.template_path(name);
+        if !path.exists() {
+            return Err(WeeklyError::TemplateNotFound(name.to_string()));
+        }
+        let invalid = |reason: String| WeeklyError::InvalidTemplate { name: name.to_string(), reason };
+        let content = fs::read_to_string(&path).map_err(|e| invalid(e.to_string()))?;
+        let template: TemplateFile = serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+        
+        let mut pool = match mode {
+            ImportMode::Append => self.activities.clone(),
+            ImportMode::Replace => Vec::new(),
+        };
+        let mut problems = Vec::new();
+        for item in &template.activities {
+            let mut activity = item.to_activity();
+            activity.title = self.title_normalization.apply(&activity.title);
+            match self.validate_activity_in(&activity, &pool) {
+                Ok(()) => pool.push(activity),
+                Err(e) => problems.push(format!("'{}' ({} {}): {}", activity.title, day_name(activity.day), activity.start_time, e)),
+            }
+        }
+        if !problems.is_empty() {
+            return Err(WeeklyError::TemplateConflicts(problems));
+        }
+        
+        self.record_undo();
+        self.activities = pool;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Horários da grade em que uma atividade da duração cabe sem conflitos em `pool`
+    fn free_slots_in(&self, pool: &[Activity], day: Weekday, duration: f32) -> Vec<String> {
+        let length = duration_minutes(duration);
+        self.generate_time_slots().into_iter()
+            .filter(|slot| {
+                let start = parse_minutes(slot).unwrap();
+                let end = start + length;
+                end <= self.end_hour * 60 && self.overlapping_in(pool, day, start, end, None).is_empty()
+            })
+            .collect()
+    }
+    
+    // Sugerir (id, dia, horário) para realocar atividades em conflito; nada é aplicado
+    pub fn suggest_resolution(&self) -> Vec<(String, String, String)> {
+        let mut pool = self.activities.clone();
+        let mut suggestions = Vec::new();
+        
+        // As atividades mais recentes cedem lugar às que já estavam na agenda
+        let mut order: Vec<&Activity> = self.activities.iter().collect();
+        order.sort_by_key(|a| std::cmp::Reverse(a.created_at));
+        
+        for original in order {
+            let (start, end) = match (original.start_minutes(), original.end_minutes()) {
+                (Some(start), Some(end)) => (start, end),
+                _ => continue,
+            };
+            if self.overlapping_in(&pool, original.day, start, end, Some(&original.id)).is_empty() {
+                continue;
+            }
+            
+            let index = pool.iter().position(|a| a.id == original.id).unwrap();
+            let mut moved = pool.remove(index);
+            
+            // Mesmo dia primeiro, depois os demais na ordem da semana
+            let day_position = original.day.num_days_from_monday() as usize;
+            let candidate_days = WEEKDAYS.iter().cycle().skip(day_position).take(WEEKDAYS.len());
+            
+            for &day in candidate_days {
+                let nearest = self.free_slots_in(&pool, day, moved.duration).into_iter()
+                    .min_by_key(|slot| (parse_minutes(slot).unwrap() - start).abs());
+                
+                if let Some(slot) = nearest {
+                    moved.day = day;
+                    moved.start_time = slot.clone();
+                    suggestions.push((moved.id.clone(), day_name(day).to_string(), slot));
+                    break;
+                }
+            }
+            pool.push(moved);
+        }
+        
+        suggestions
+    }
+    
+    // Definir a cor usada para categorias sem cor válida
+    pub fn set_fallback_color(&mut self, color: &str) -> Result<(), String> {
+        if parse_hex_color(color).is_none() {
+            return Err(format!("Cor inválida: {}. Use o formato #RRGGBB", color));
+        }
+        self.fallback_color = color.to_uppercase();
+        Ok(())
+    }
+    
+    // Cor de uma categoria, ou a cor reserva se ela não existir ou for inválida
+    pub fn category_color(&self, category_key: &str) -> &str {
+        match self.categories.get(category_key) {
+            Some(category) if parse_hex_color(&category.color).is_some() => &category.color,
+            _ => &self.fallback_color,
+        }
+    }
+    
+    // Idioma atual dos dias
+    pub fn locale(&self) -> &Locale {
+        &self.locale
+    }
+    
+    // Trocar o idioma dos dias; os dados salvos não mudam
+    pub fn set_locale(&mut self, locale: Locale) {
+        self.locale = locale;
+    }
+    
+    // Texto tingido com a cor da categoria (256 cores ANSI), se as cores estiverem ativas
+    pub fn colorize(&self, text: &str, category_key: &str) -> String {
+        match ansi_256(self.category_color(category_key)) {
+            Some(code) if self.ansi_colors => format!("\x1b[38;5;{}m{}\x1b[0m", code, text),
+            _ => text.to_string(),
+        }
+    }
+    
+    // Ativar ou desativar as cores ANSI (padrão: só em terminal e sem NO_COLOR)
+    pub fn set_ansi_colors(&mut self, enabled: bool) {
+        self.ansi_colors = enabled;
+    }
+    
+    // Cor de texto (preto ou branco) legível sobre o fundo informado, pela luminância relativa
+    pub fn contrast_text_color(background: &str) -> &'static str {
+        let (r, g, b) = match parse_hex_color(background) {
+            Some(rgb) => rgb,
+            None => return "#000000",
+        };
+        
+        let linear = |channel: u8| {
+            let c = channel as f32 / 255.0;
+            if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+        let luminance = 0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b);
+        
+        if luminance > 0.179 { "#000000" } else { "#FFFFFF" }
+    }
+    
+    // Em quantos dias da semana (0–7) cada horário da grade está ocupado
+    pub fn slot_occupancy(&self) -> HashMap<String, u8> {
+        self.generate_time_slots().into_iter()
+            .map(|slot| {
+                let start = parse_minutes(&slot).unwrap();
+                let busy_days = WEEKDAYS.iter()
+                    .filter(|day| !self.overlapping_in(&self.activities, **day, start, start + self.slot_minutes as i32, None).is_empty())
+                    .count();
+                (slot, busy_days as u8)
+            })
+            .collect()
+    }
+    
+    // Horário médio de início por categoria ("HH:MM")
+    pub fn average_start_time_by_category(&self) -> HashMap<String, String> {
+        let mut totals: HashMap<String, (i32, i32)> = HashMap::new();
+        for activity in self.live_activities() {
+            if let Some(minutes) = activity.start_minutes() {
+                let entry = totals.entry(activity.category.clone()).or_insert((0, 0));
+                entry.0 += minutes;
+                entry.1 += 1;
+            }
+        }
+        
+        totals.into_iter()
+            .map(|(category, (sum, count))| {
+                let average = (sum as f32 / count as f32).round() as i32;
+                (category, format_minutes(average))
+            })
+            .collect()
+    }
+    
+    // Dias sem nenhum horário livre na grade (provável excesso ou erro de agendamento)
+    pub fn fully_booked_days(&self) -> Vec<String> {
+        WEEKDAYS.iter()
+            .filter(|day| self.free_slots_in(&self.activities, **day, self.slot_minutes as f32 / 60.0).is_empty())
+            .map(|day| day_name(*day).to_string())
+            .collect()
+    }
+    
+    // Definir (ou limpar) o nível de esforço de uma atividade
+    pub fn set_effort(&mut self, id: &str, effort: Option<u8>) -> Result<(), String> {
+        let changes = ActivityPatch { effort: Some(effort), ..ActivityPatch::default() };
+        Ok(self.edit_activity_with(id, changes)?)
+    }
+    
+    // Soma do esforço de todas as atividades da semana
+    pub fn total_effort(&self) -> u32 {
+        self.live_activities()
+            .filter_map(|a| a.effort)
+            .map(|effort| effort as u32)
+            .sum()
+    }
+    
+    // Total de minutos na semana em que duas ou mais atividades coincidem
+    pub fn overlapping_time(&self) -> f32 {
+        const WEEK: i32 = 7 * 24 * 60;
+        
+        // Eventos de início (+1) e fim (-1) na semana inteira, para pegar sobreposições
+        // que atravessam a meia-noite; fins vêm antes de inícios no mesmo minuto
+        let mut events: Vec<(i32, i32)> = Vec::new();
+        for activity in self.live_activities() {
+            let day = activity.day.num_days_from_monday() as i32 * 24 * 60;
+            if let (Some(start), Some(end)) = (activity.start_minutes(), activity.end_minutes()) {
+                let (start, end) = (day + start, day + end);
+                // O que passa do fim de Domingo continua em Segunda
+                if end > WEEK {
+                    events.extend([(start, 1), (WEEK, -1), (0, 1), (end - WEEK, -1)]);
+                } else {
+                    events.extend([(start, 1), (end, -1)]);
+                }
+            }
+        }
+        events.sort();
+        
+        let mut total = 0;
+        let mut active = 0;
+        let mut previous = 0;
+        for (minute, delta) in events {
+            if active >= 2 {
+                total += minute - previous;
+            }
+            active += delta;
+            previous = minute;
+        }
+        total as f32
+    }
+    
+    // Assinatura estável da estrutura da semana (dia, início, duração, categoria),
+    // ignorando IDs, títulos e datas de criação
+    pub fn shape_signature(&self) -> String {
+        let mut shape: Vec<(usize, String, String, String)> = self.live_activities()
+            .map(|a| (
+                a.day.num_days_from_monday() as usize,
+                a.start_time.clone(),
+                format!("{:.2}", a.duration),
+                a.category.clone(),
+            ))
+            .collect();
+        shape.sort();
+        
+        let canonical: Vec<String> = shape.iter()
+            .map(|(day, start, duration, category)| format!("{}|{}|{}|{}", day, start, duration, category))
+            .collect();
+        
+        // FNV-1a de 64 bits: estável entre execuções e versões do compilador
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in canonical.join(";").bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}", hash)
+    }
+    
+    // Atividades agrupadas por horário de início, só para horários usados em 2 ou mais dias
+    pub fn recurring_time_patterns(&self) -> HashMap<String, Vec<&Activity>> {
+        let mut by_time: HashMap<String, Vec<&Activity>> = HashMap::new();
+        for activity in self.get_all_activities() {
+            by_time.entry(activity.start_time.clone()).or_default().push(activity);
+        }
+        
+        by_time.retain(|_, activities| {
+            let mut days: Vec<u32> = activities.iter().map(|a| a.day.num_days_from_monday()).collect();
+            days.sort();
+            days.dedup();
+            days.len() >= 2
+        });
+        by_time
+    }
+    
+    // Mudanças de local entre atividades consecutivas do dia: (de, para, horário de saída)
+    pub fn estimated_transitions(&self, day: &str) -> Vec<(String, String, String)> {
+        self.location_changes(day).into_iter()
+            .map(|(from, to, leave_at, _)| (from, to, leave_at))
+            .collect()
+    }
+    
+    // Mudanças de local sem nenhum intervalo para o deslocamento (provavelmente impossíveis)
+    pub fn impossible_transitions(&self, day: &str) -> Vec<(String, String, String)> {
+        self.location_changes(day).into_iter()
+            .filter(|(_, _, _, gap)| *gap <= 0)
+            .map(|(from, to, leave_at, _)| (from, to, leave_at))
+            .collect()
+    }
+    
+    // (de, para, horário de saída, minutos livres até a próxima atividade)
+    fn location_changes(&self, day: &str) -> Vec<(String, String, String, i32)> {
+        let activities = self.get_activities_by_day(day);
+        activities.windows(2)
+            .filter_map(|pair| {
+                let (from, to) = (pair[0].location.as_ref()?, pair[1].location.as_ref()?);
+                if from.trim().eq_ignore_ascii_case(to.trim()) {
+                    return None;
+                }
+                let leave_at = pair[0].end_minutes()?;
+                let gap = pair[1].start_minutes()? - leave_at;
+                Some((from.clone(), to.clone(), format_minutes(leave_at), gap))
+            })
+            .collect()
+    }
+    
+    // Adicionar uma faixa de silêncio (ex.: 12:00–13:00 ou 22:00–06:00)
+    pub fn add_quiet_hours(&mut self, from: &str, to: &str) -> Result<(), String> {
+        self.validate_time(from)?;
+        self.validate_time(to)?;
+        if from == to {
+            return Err("A faixa de silêncio precisa ter início e fim diferentes".to_string());
+        }
+        
+        self.quiet_hours.push((from.to_string(), to.to_string()));
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Remover todas as faixas de silêncio
+    pub fn clear_quiet_hours(&mut self) {
+        self.quiet_hours.clear();
+        
+        // Salvar automaticamente
+        self.persist();
+    }
+    
+    // Faixas de silêncio configuradas
+    pub fn quiet_hours(&self) -> &[(String, String)] {
+        &self.quiet_hours
+    }
+    
+    // Primeira faixa de silêncio que intersecta [start, end) (em minutos, podendo passar de 24:00)
+    fn quiet_hours_overlap(&self, start: i32, end: i32) -> Option<&(String, String)> {
+        const DAY: i32 = 24 * 60;
+        
+        // Trecho do dia de início e, se houver, trecho após a meia-noite
+        let mut spans = vec![(start, end.min(DAY))];
+        if end > DAY {
+            spans.push((0, end - DAY));
+        }
+        
+        self.quiet_hours.iter().find(|(from, to)| {
+            let (from, to) = match (parse_minutes(from), parse_minutes(to)) {
+                (Some(from), Some(to)) => (from, to),
+                _ => return false,
+            };
+            let ranges = if from < to { vec![(from, to)] } else { vec![(from, DAY), (0, to)] };
+            
+            spans.iter().any(|(s, e)| ranges.iter().any(|(qs, qe)| s < qe && e > qs))
+        })
+    }
+    
+    // Deslocar todas as atividades de uma categoria (tudo ou nada), retornando quantas mudaram
+    pub fn shift_category(&mut self, category: &str, offset_minutes: i32) -> Result<usize, String> {
+        if !self.categories.contains_key(category) {
+            return Err(format!("Categoria '{}' não existe", category));
+        }
+        
+        let mut shifted = self.activities.clone();
+        let mut moved = 0;
+        for activity in shifted.iter_mut().filter(|a| a.category == category) {
+            let start = activity.start_minutes()
+                .ok_or_else(|| format!("Horário inválido: {}", activity.start_time))? + offset_minutes;
+            let end = start + duration_minutes(activity.duration);
+            
+            if start < self.start_hour * 60 || end > self.end_hour * 60 {
+                return Err(format!("'{}' ({}) sairia da grade de {:02}:00 às {:02}:00", activity.title, day_name(activity.day), self.start_hour, self.end_hour));
+            }
+            
+            activity.start_time = format_minutes(start);
+            moved += 1;
+        }
+        
+        // Validar o resultado final antes de aplicar
+        for activity in shifted.iter().filter(|a| a.category == category) {
+            self.validate_activity_in(activity, &shifted)
+                .map_err(|e| format!("'{}' ({}): {}", activity.title, day_name(activity.day), e))?;
+        }
+        
+        self.record_undo();
+        self.activities = shifted;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(moved)
+    }
+    
+    // Indica se uma atividade da duração informada caberia no dia/horário sem conflitos
+    pub fn is_slot_free(&self, day: &str, start_time: &str, duration: f32) -> bool {
+        let day = match self.resolve_day(day) {
+            Ok(day) => day,
+            Err(_) => return false,
+        };
+        if self.validate_time(start_time).is_err()
+            || self.validate_slot(start_time).is_err()
+            || self.validate_duration(duration).is_err()
+        {
+            return false;
+        }
+        
+        let start = parse_minutes(start_time).unwrap();
+        let end = start + duration_minutes(duration);
+        self.quiet_hours_overlap(start, end).is_none()
+            && self.overlapping_in(&self.activities, day, start, end, None).is_empty()
+    }
+    
+    // Cobertura do dia: cada horário da grade com a atividade que o ocupa (se houver)
+    pub fn day_timeline(&self, day: &str) -> Vec<(String, Option<&Activity>)> {
+        let day = match self.resolve_day(day) {
+            Ok(day) => day,
+            Err(_) => return Vec::new(),
+        };
+        self.generate_time_slots().into_iter()
+            .map(|slot| {
+                let start = parse_minutes(&slot).unwrap();
+                let covering = self.overlapping_in(&self.activities, day, start, start + self.slot_minutes as i32, None)
+                    .into_iter()
+                    .next();
+                (slot, covering)
+            })
+            .collect()
+    }
+    
+    // Agenda do dia em formato de régua vertical, pronta para impressão
+    pub fn format_day_timeline(&self, day: &str) -> String {
+        let mut output = format!("=== {} ===\n", day);
+        let mut previous_id: Option<&str> = None;
+        
+        for (slot, activity) in self.day_timeline(day) {
+            match activity {
+                Some(act) if previous_id == Some(act.id.as_str()) => {
+                    output.push_str(&format!("{} │████\n", slot));
+                }
+                Some(act) => {
+                    output.push_str(&format!("{} │████ {} ({})\n", slot, act.title, Self::format_time(act.duration)));
+                }
+                None => output.push_str(&format!("{} │\n", slot)),
+            }
+            previous_id = activity.map(|a| a.id.as_str());
+        }
+        output
+    }
+    
+    // Definir como a grade exibe atividades que começam no mesmo horário
+    pub fn set_grid_collision_display(&mut self, display: GridCollisionDisplay) {
+        self.grid_collision_display = display;
+    }
+    
+    // Blocos contínuos (início, fim) de uma categoria em um dia
+    fn category_blocks(&self, category: &str, day: &str) -> Vec<(i32, i32)> {
+        let mut blocks: Vec<(i32, i32)> = Vec::new();
+        for activity in self.get_activities_by_day(day).into_iter().filter(|a| a.category == category) {
+            let (start, end) = match (activity.start_minutes(), activity.end_minutes()) {
+                (Some(start), Some(end)) => (start, end),
+                _ => continue,
+            };
+            match blocks.last_mut() {
+                Some(block) if start <= block.1 => block.1 = block.1.max(end),
+                _ => blocks.push((start, end)),
+            }
+        }
+        blocks
+    }
+    
+    // Quantidade de blocos separados por categoria na semana (quanto maior, mais fragmentado)
+    pub fn fragmentation_report(&self) -> HashMap<String, usize> {
+        let mut report = HashMap::new();
+        for category in self.live_activities().map(|a| a.category.as_str()) {
+            if report.contains_key(category) {
+                continue;
+            }
+            let blocks = WEEK_DAYS.iter()
+                .map(|day| self.category_blocks(category, day).len())
+                .sum();
+            report.insert(category.to_string(), blocks);
+        }
+        report
+    }
+    
+    // Sugerir (dia, horário) para juntar em um só bloco o tempo fragmentado da categoria em cada dia
+    pub fn suggest_consolidation(&self, category: &str) -> Vec<(String, String)> {
+        let mut suggestions = Vec::new();
+        for day in WEEKDAYS {
+            let blocks = self.category_blocks(category, day_name(day));
+            if blocks.len() < 2 {
+                continue;
+            }
+            
+            // Liberar o tempo da categoria no dia e procurar um espaço para o total
+            let total_hours: f32 = blocks.iter().map(|(start, end)| (end - start) as f32 / 60.0).sum();
+            let pool: Vec<Activity> = self.activities.iter()
+                .filter(|a| !(a.day == day && a.category == category))
+                .cloned()
+                .collect();
+            
+            let first_start = blocks[0].0;
+            if let Some(slot) = self.free_slots_in(&pool, day, total_hours).into_iter()
+                .min_by_key(|slot| (parse_minutes(slot).unwrap() - first_start).abs())
+            {
+                suggestions.push((day_name(day).to_string(), slot));
+            }
+        }
+        suggestions
+    }
+    
+    // Guardar na lista de espera uma atividade que não coube na agenda
+    pub fn add_to_waitlist(&mut self, params: ActivityParams) -> Result<(), String> {
+        if params.title.trim().is_empty() {
+            return Err("Título não pode estar vazio".to_string());
+        }
+        if !self.categories.contains_key(&params.category) {
+            return Err(format!("Categoria '{}' não existe", params.category));
+        }
+        
+        self.record_undo();
+        self.waitlist.push(params);
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Itens aguardando na lista de espera, na ordem de chegada
+    pub fn waitlist(&self) -> &[ActivityParams] {
+        &self.waitlist
+    }
+    
+    // Retirar um item da lista de espera pela posição
+    pub fn remove_from_waitlist(&mut self, index: usize) -> Result<ActivityParams, String> {
+        if index >= self.waitlist.len() {
+            return Err("Item da lista de espera não encontrado".to_string());
+        }
+        self.record_undo();
+        let params = self.waitlist.remove(index);
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(params)
+    }
+    
+    // Agendar o que for possível da lista de espera: no horário pedido ou, se ocupado,
+    // no horário livre mais próximo do mesmo dia. Retorna os IDs criados
+    pub fn try_schedule_waitlist(&mut self) -> Vec<String> {
+        let before = self.history_state();
+        let scheduled = self.schedule_waitlist();
+        if !scheduled.is_empty() {
+            self.push_undo(before);
+        }
+        scheduled
+    }
+    
+    // Encaixar a lista de espera sem registrar desfazer (quem chama decide o passo)
+    fn schedule_waitlist(&mut self) -> Vec<String> {
+        let mut scheduled = Vec::new();
+        let mut remaining = Vec::new();
+        
+        for params in std::mem::take(&mut self.waitlist) {
+            let mut activity = params.to_activity();
+            activity.title = self.title_normalization.apply(&activity.title);
+            let mut placed = self.validate_activity(&activity).is_ok();
+            
+            if !placed {
+                let requested = parse_minutes(&params.start_time).unwrap_or(self.start_hour * 60);
+                let mut slots = self.free_slots_in(&self.activities, params.day, params.duration);
+                slots.sort_by_key(|slot| (parse_minutes(slot).unwrap() - requested).abs());
+                
+                for slot in slots {
+                    activity.start_time = slot;
+                    if self.validate_activity(&activity).is_ok() {
+                        placed = true;
+                        break;
+                    }
+                }
+            }
+            
+            if placed {
+                scheduled.push(activity.id.clone());
+                self.activities.push(activity);
+                self.invalidate_day_index();
+            } else {
+                remaining.push(params);
+            }
+        }
+        
+        self.waitlist = remaining;
+        if !scheduled.is_empty() {
+            // Salvar automaticamente
+            self.persist();
+        }
+        scheduled
+    }
+    
+    // Tempo total por local (atividades sem local ficam em "Sem local")
+    pub fn time_by_location(&self) -> HashMap<String, f32> {
+        let mut minutes: HashMap<String, i32> = HashMap::new();
+        for activity in self.live_activities() {
+            let location = match activity.location.as_deref().map(str::trim) {
+                Some(location) if !location.is_empty() => location.to_string(),
+                _ => "Sem local".to_string(),
+            };
+            *minutes.entry(location).or_insert(0) += duration_minutes(activity.duration);
+        }
+        
+        minutes.into_iter()
+            .map(|(location, total)| (location, total as f32 / 60.0))
+            .collect()
+    }
+    
+    // Definir uma meta semanal mínima de horas para uma categoria
+    pub fn set_goal(&mut self, category: &str, target_hours: f32) -> Result<(), String> {
+        if !self.categories.contains_key(category) {
+            return Err(format!("Categoria '{}' não existe", category));
+        }
+        if target_hours <= 0.0 {
+            return Err("A meta deve ser maior que zero".to_string());
+        }
+        
+        self.goals.insert(category.to_string(), target_hours);
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Remover a meta de uma categoria
+    pub fn remove_goal(&mut self, category: &str) -> Result<(), String> {
+        if self.goals.remove(category).is_none() {
+            return Err(format!("Categoria '{}' não tem meta definida", category));
+        }
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Comparar as metas com o tempo agendado de cada categoria
+    pub fn goal_progress(&self) -> Vec<GoalStatus> {
+        let stats = self.calculate_weekly_stats();
+        let mut progress: Vec<GoalStatus> = self.goals.iter()
+            .map(|(category, target)| {
+                let actual = stats.by_category.get(category).copied().unwrap_or(0.0);
+                GoalStatus {
+                    category: category.clone(),
+                    target_hours: *target,
+                    actual_hours: actual,
+                    remaining_hours: (target - actual).max(0.0),
+                    percentage: actual / target * 100.0,
+                }
+            })
+            .collect();
+        progress.sort_by(|a, b| a.category.cmp(&b.category));
+        progress
+    }
+    
+    // Deslocar todas as atividades `days` dias na semana (negativo volta), com volta cíclica
+    pub fn rotate_week(&mut self, days: i32) -> Result<(), String> {
+        let offset = days.rem_euclid(7) as usize;
+        if offset == 0 {
+            return Ok(());
+        }
+        
+        let mut rotated = self.activities.clone();
+        for activity in rotated.iter_mut() {
+            let index = activity.day.num_days_from_monday() as usize;
+            activity.day = WEEKDAYS[(index + offset) % 7];
+        }
+        
+        // Validar o resultado final antes de aplicar
+        for activity in &rotated {
+            self.validate_activity_in(activity, &rotated)
+                .map_err(|e| format!("'{}' ({}): {}", activity.title, day_name(activity.day), e))?;
+        }
+        
+        self.record_undo();
+        self.activities = rotated;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Títulos mais frequentes (sem diferenciar maiúsculas), do mais comum para o menos comum
+    pub fn most_frequent_titles(&self, top_n: usize) -> Vec<(String, usize)> {
+        // Chave em minúsculas → (grafia da primeira ocorrência, contagem)
+        let mut counts: HashMap<String, (String, usize)> = HashMap::new();
+        for activity in self.live_activities() {
+            let title = activity.title.trim();
+            counts.entry(title.to_lowercase())
+                .or_insert_with(|| (title.to_string(), 0))
+                .1 += 1;
+        }
+        
+        let mut titles: Vec<(String, usize)> = counts.into_values().collect();
+        titles.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        titles.truncate(top_n);
+        titles
+    }
+    
+    // Definir como os títulos são normalizados em add_activity/edit_activity
+    pub fn set_title_normalization(&mut self, normalization: TitleNormalization) {
+        self.title_normalization = normalization;
+    }
+    
+    // Exportar a semana em Graphviz/DOT: um cluster por dia com as atividades encadeadas por horário
+    pub fn export_to_dot(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+        
+        let mut content = String::new();
+        content.push_str("digraph semana {\n");
+        content.push_str("    rankdir=LR;\n");
+        content.push_str("    node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\"];\n");
+        
+        for (day_index, day) in WEEK_DAYS.iter().enumerate() {
+            let activities = self.get_activities_by_day(day);
+            if activities.is_empty() {
+                continue;
+            }
+            
+            content.push_str(&format!("\n    subgraph cluster_{} {{\n", day_index));
+            content.push_str(&format!("        label=\"{}\";\n", escape(day)));
+            
+            for (index, activity) in activities.iter().enumerate() {
+                let color = self.category_color(&activity.category);
+                content.push_str(&format!(
+                    "        d{}_{} [label=\"{}–{}\\n{}\", fillcolor=\"{}\", fontcolor=\"{}\"];\n",
+                    day_index,
+                    index,
+                    activity.start_time,
+                    activity.end_time(),
+                    escape(&activity.title),
+                    color,
+                    Self::contrast_text_color(color)
+                ));
+            }
+            
+            if activities.len() > 1 {
+                let chain: Vec<String> = (0..activities.len())
+                    .map(|index| format!("d{}_{}", day_index, index))
+                    .collect();
+                content.push_str(&format!("        {};\n", chain.join(" -> ")));
+            }
+            
+            content.push_str("    }\n");
+        }
+        
+        content.push_str("}\n");
+        
+        fs::write(filename, content)?;
+        Ok(())
+    }
+    
+    // Exportar a grade semanal em HTML: linhas são os horários, colunas os dias e cada
+    // atividade ocupa (rowspan) os slots que dura, com a cor da sua categoria.
+    // Atividades sobrepostas no mesmo dia dividem uma célula
+    pub fn export_to_html(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let escape = |text: &str| text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#39;");
+        let time_slots = self.generate_time_slots();
+        let grid_start = self.start_hour * 60;
+        let slot = self.slot_minutes as i32;
+        
+        // Por dia: células (linha inicial, quantidade de linhas, atividades)
+        let mut cells: Vec<Vec<(usize, usize, Vec<&Activity>)>> = Vec::new();
+        for day in WEEK_DAYS {
+            let mut day_cells: Vec<(usize, usize, Vec<&Activity>)> = Vec::new();
+            for activity in self.get_activities_by_day(day) {
+                let (start, end) = match (activity.start_minutes(), activity.end_minutes()) {
+                    (Some(start), Some(end)) if start >= grid_start && start < self.end_hour * 60 => (start, end),
+                    _ => continue,
+                };
+                let row = ((start - grid_start) / slot) as usize;
+                let end_row = (((end - grid_start + slot - 1) / slot) as usize).min(time_slots.len()).max(row + 1);
+                match day_cells.last_mut() {
+                    // Começa dentro da célula anterior: juntar a ela
+                    Some((first_row, rows, activities)) if row < *first_row + *rows => {
+                        *rows = (*rows).max(end_row - *first_row);
+                        activities.push(activity);
+                    }
+                    _ => day_cells.push((row, end_row - row, vec![activity])),
+                }
+            }
+            cells.push(day_cells);
+        }
+        
+        let mut content = String::new();
+        content.push_str("<!DOCTYPE html>\n<html lang=\"pt-BR\">\n<head>\n<meta charset=\"utf-8\">\n");
+        content.push_str("<title>Organizador Semanal</title>\n<style>\n");
+        content.push_str("table { border-collapse: collapse; font-family: sans-serif; font-size: 12px; }\n");
+        content.push_str("th, td { border: 1px solid #ccc; padding: 2px 6px; min-width: 110px; vertical-align: top; }\n");
+        content.push_str("</style>\n</head>\n<body>\n<table>\n<tr><th>Horário</th>");
+        for day in WEEK_DAYS {
+            content.push_str(&format!("<th>{}</th>", day));
+        }
+        content.push_str("</tr>\n");
+        
+        for (row, time) in time_slots.iter().enumerate() {
+            content.push_str(&format!("<tr><th>{}</th>", time));
+            for day_cells in &cells {
+                match day_cells.iter().find(|(first_row, rows, _)| (*first_row..first_row + rows).contains(&row)) {
+                    Some((first_row, rows, activities)) if *first_row == row => {
+                        let color = self.category_color(&activities[0].category);
+                        let entries: Vec<String> = activities.iter()
+                            .map(|activity| {
+                                let mut entry = format!("<strong>{}</strong><br>{}–{}", escape(&activity.title), activity.start_time, activity.end_time());
+                                if let Some(location) = &activity.location {
+                                    entry.push_str(&format!("<br>{}", escape(location)));
+                                }
+                                if let Some(description) = &activity.description {
+                                    entry.push_str(&format!("<br><small>{}</small>", escape(description)));
+                                }
+                                entry
+                            })
+                            .collect();
+                        content.push_str(&format!(
+                            "<td rowspan=\"{}\" style=\"background:{};color:{}\">{}</td>",
+                            rows,
+                            color,
+                            Self::contrast_text_color(color),
+                            entries.join("<hr>")
+                        ));
+                    }
+                    Some(_) => {} // Coberta pelo rowspan de uma célula acima
+                    None => content.push_str("<td></td>"),
+                }
+            }
+            content.push_str("</tr>\n");
+        }
+        
+        content.push_str("</table>\n</body>\n</html>\n");
+        
+        fs::write(filename, content)?;
+        Ok(())
+    }
+    
+    // Exportar em Markdown: uma seção por dia com a tabela de atividades e um resumo no fim
+    pub fn export_to_markdown(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.export_to_markdown_filtered(filename, |_| true)
+    }
+    
+    // Exportar em Markdown apenas as atividades (não canceladas) que satisfazem o filtro
+    pub fn export_to_markdown_filtered(&self, filename: &str, predicate: impl Fn(&Activity) -> bool) -> Result<(), Box<dyn std::error::Error>> {
+        // "|" quebraria a tabela e quebras de linha encerrariam a linha
+        let escape = |text: &str| text.replace('|', "\\|").replace(['\r', '\n'], " ");
+        let category_name = |key: &str| self.categories.get(key).map_or(key, |c| c.name.as_str()).to_string();
+        
+        let mut content = String::from("# Organizador Semanal\n");
+        let mut total_minutes = 0;
+        let mut count = 0;
+        let mut by_category: HashMap<String, i32> = HashMap::new();
+        
+        for day in WEEK_DAYS {
+            content.push_str(&format!("\n## {}\n\n", day));
+            
+            let activities: Vec<&Activity> = self.get_activities_by_day(day).into_iter()
+                .filter(|a| predicate(a))
+                .collect();
+            if activities.is_empty() {
+                content.push_str("_sem atividades_\n");
+                continue;
+            }
+            
+            content.push_str("| Horário | Título | Duração | Categoria | Local |\n");
+            content.push_str("|---|---|---|---|---|\n");
+            for activity in activities {
+                content.push_str(&format!(
+                    "| {}–{} | {} | {} | {} | {} |\n",
+                    activity.start_time,
+                    activity.end_time(),
+                    escape(&activity.title),
+                    Self::format_time(activity.duration),
+                    escape(&category_name(&activity.category)),
+                    escape(activity.location.as_deref().unwrap_or(""))
+                ));
+                
+                let minutes = duration_minutes(activity.duration);
+                total_minutes += minutes;
+                count += 1;
+                *by_category.entry(activity.category.clone()).or_insert(0) += minutes;
+            }
+        }
+        
+        content.push_str("\n## Resumo\n\n");
+        content.push_str(&format!("- Atividades: {}\n", count));
+        content.push_str(&format!("- Tempo total: {}\n", Self::format_time(total_minutes as f32 / 60.0)));
+        if !by_category.is_empty() {
+            let mut categories: Vec<(String, i32)> = by_category.into_iter().collect();
+            categories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            let times: Vec<f32> = categories.iter().map(|(_, minutes)| *minutes as f32).collect();
+            let percentages = apportion_percentages(&times, self.stats_precision);
+            
+            content.push_str("- Por categoria:\n");
+            for ((key, minutes), percentage) in categories.iter().zip(percentages) {
+                content.push_str(&format!(
+                    "  - {}: {} ({:.*}%)\n",
+                    category_name(key),
+                    Self::format_time(*minutes as f32 / 60.0),
+                    self.stats_precision,
+                    percentage
+                ));
+            }
+        }
+        
+        fs::write(filename, content)?;
+        Ok(())
+    }
+    
+    // Calcular o que a remoção de uma atividade liberaria, sem remover nada
+    pub fn removal_impact(&self, id: &str) -> Result<RemovalImpact, String> {
+        let activity = self.get_activity(id)
+            .ok_or("Atividade não encontrada")?;
+        let pool: Vec<Activity> = self.live_activities()
+            .filter(|a| a.id != id)
+            .cloned()
+            .collect();
+        
+        // Intervalo livre: do fim da atividade anterior ao início da próxima (ou borda da grade)
+        let free_gap = match (activity.start_minutes(), activity.end_minutes()) {
+            (Some(start), Some(end)) => {
+                let neighbors = pool.iter()
+                    .filter(|a| a.day == activity.day)
+                    .filter_map(|a| Some((a.start_minutes()?, a.end_minutes()?)));
+                let mut gap_start = (self.start_hour * 60).min(start);
+                let mut gap_end = (self.end_hour * 60).max(end);
+                for (other_start, other_end) in neighbors {
+                    if other_start <= start {
+                        gap_start = gap_start.max(other_end);
+                    } else {
+                        gap_end = gap_end.min(other_start);
+                    }
+                }
+                if gap_start < gap_end {
+                    Some((format_minutes(gap_start), format_minutes(gap_end)))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        
+        // O item cabe no horário pedido ou em outro slot do dia
+        let fits = |params: &ActivityParams, pool: &[Activity]| {
+            let mut candidate = params.to_activity();
+            if self.validate_activity_in(&candidate, pool).is_ok() {
+                return true;
+            }
+            self.free_slots_in(pool, params.day, params.duration).into_iter().any(|slot| {
+                candidate.start_time = slot;
+                self.validate_activity_in(&candidate, pool).is_ok()
+            })
+        };
+        
+        // Só os itens que a remoção faz caber (os que já cabem hoje não dependem dela)
+        let waitlist_fits = self.waitlist.iter()
+            .enumerate()
+            .filter(|(_, params)| fits(params, &pool) && !fits(params, &self.activities))
+            .map(|(index, _)| index)
+            .collect();
+        
+        Ok(RemovalImpact {
+            freed_hours: activity.duration,
+            free_gap,
+            waitlist_fits,
+        })
+    }
+    
+    // Buscar atividades ordenadas por relevância: o campo pesa mais que o tipo de acerto
+    // (título > categoria > local/descrição; exato > prefixo > trecho)
+    pub fn search_ranked(&self, query: &str) -> Vec<&Activity> {
+        let query_lower = query.trim().to_lowercase();
+        if query_lower.is_empty() {
+            return Vec::new();
+        }
+        
+        let quality = |text: &str| {
+            let text = text.trim().to_lowercase();
+            if text == query_lower {
+                3
+            } else if text.starts_with(&query_lower) {
+                2
+            } else if text.contains(&query_lower) {
+                1
+            } else {
+                0
+            }
+        };
+        
+        let mut scored: Vec<(u32, &Activity)> = self.get_all_activities().into_iter()
+            .filter_map(|activity| {
+                let category_name = self.categories.get(&activity.category).map_or("", |c| c.name.as_str());
+                let fields = [
+                    (3, quality(&activity.title)),
+                    (2, quality(&activity.category).max(quality(category_name))),
+                    (1, activity.location.as_deref().map_or(0, &quality)),
+                    (1, activity.description.as_deref().map_or(0, &quality)),
+                ];
+                fields.iter()
+                    .filter(|(_, hit)| *hit > 0)
+                    .map(|(weight, hit)| weight * 3 + hit)
+                    .max()
+                    .map(|score| (score, activity))
+            })
+            .collect();
+        
+        // Ordenação estável: empates mantêm a ordem da semana
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, activity)| activity).collect()
+    }
+    
+    // Comparar as atividades em memória com as do arquivo de dados (adicionadas, removidas, alteradas)
+    pub fn unsaved_changes(&self) -> Result<Vec<Change>, Box<dyn std::error::Error>> {
+        self.check_data_file_path()?;
+        
+        let saved: Vec<Activity> = if Path::new(&self.data_file).exists() {
+            let content = fs::read_to_string(&self.data_file)?;
+            parse_save_data(&content)?.schedule_activities(&self.active_schedule)
+        } else {
+            Vec::new()
+        };
+        let saved_by_id: HashMap<&str, &Activity> = saved.iter()
+            .map(|a| (a.id.as_str(), a))
+            .collect();
+        
+        let mut changes = Vec::new();
+        for activity in &self.activities {
+            match saved_by_id.get(activity.id.as_str()) {
+                None => changes.push(Change::Added(activity.clone())),
+                Some(before) => {
+                    if serde_json::to_value(before)? != serde_json::to_value(activity)? {
+                        changes.push(Change::Modified { before: Box::new((*before).clone()), after: Box::new(activity.clone()) });
+                    }
+                }
+            }
+        }
+        for activity in &saved {
+            if !self.activities.iter().any(|a| a.id == activity.id) {
+                changes.push(Change::Removed(activity.clone()));
+            }
+        }
+        
+        Ok(changes)
+    }
+    
+    // Indica se `dependent` começa antes de `dependency` terminar no mesmo dia. Em dias
+    // diferentes não há ordem a checar: a semana se repete, e a dependência de outro dia
+    // pode ser a ocorrência da semana anterior
+    fn dependency_violated(dependent: &Activity, dependency: &Activity) -> bool {
+        if dependent.day != dependency.day {
+            return false;
+        }
+        match (dependent.start_minutes(), dependency.end_minutes()) {
+            (Some(start), Some(dependency_end)) => start < dependency_end,
+            _ => false,
+        }
+    }
+    
+    // Definir (ou remover, com None) a atividade que precisa terminar antes desta
+    pub fn set_dependency(&mut self, id: &str, depends_on: Option<&str>) -> Result<(), String> {
+        let mut updated = self.get_activity(id)
+            .ok_or("Atividade não encontrada")?
+            .clone();
+        updated.depends_on = depends_on.map(|d| d.to_string());
+        self.validate_activity(&updated)?;
+        
+        self.record_undo();
+        let activity = self.get_activity_mut(id).unwrap();
+        activity.depends_on = updated.depends_on;
+        
+        // Salvar automaticamente
+        self.persist();
+        
+        Ok(())
+    }
+    
+    // Pares (atividade, dependência) em que a atividade começa antes da dependência terminar no mesmo dia
+    pub fn dependency_violations(&self) -> Vec<(String, String)> {
+        self.activities.iter()
+            .filter_map(|activity| {
+                let dependency_id = activity.depends_on.as_ref()?;
+                let dependency = self.activities.iter().find(|a| a.id == *dependency_id)?;
+                Self::dependency_violated(activity, dependency)
+                    .then(|| (activity.id.clone(), dependency.id.clone()))
+            })
+            .collect()
+    }
+    
+    // Horários de início (slots da grade, em ordem) em que uma atividade da duração
+    // informada cabe no dia sem conflitos, sem horário de silêncio e sem passar do fim da grade
+    pub fn find_free_slots(&self, day: &str, duration: f32) -> Vec<String> {
+        let length = duration_minutes(duration);
+        self.generate_time_slots().into_iter()
+            .filter(|slot| {
+                let start = parse_minutes(slot).unwrap();
+                start + length <= self.end_hour * 60 && self.is_slot_free(day, slot, duration)
+            })
+            .collect()
+    }
+    
+    // Slots livres do dia para a duração informada, restritos à janela [from, to)
+    // (ex.: só dentro do horário comercial)
+    pub fn find_free_slots_in_range(&self, day: &str, duration: f32, from: &str, to: &str) -> Vec<String> {
+        let (from, to) = match (parse_minutes(from), parse_minutes(to)) {
+            (Some(from), Some(to)) if from < to => (from, to),
+            _ => return Vec::new(),
+        };
+        let length = duration_minutes(duration);
+        
+        self.find_free_slots(day, duration).into_iter()
+            .filter(|slot| {
+                let start = parse_minutes(slot).unwrap();
+                start >= from && start + length <= to
+            })
+            .collect()
+    }
+    
+    // Regularidade de uma rotina (por título ou chave de categoria): 1.0 quando todas as
+    // ocorrências começam no mesmo horário, caindo para 0.5 com desvio padrão de 1 hora.
+    // Retorna 0.0 se não houver ocorrências
+    pub fn routine_consistency(&self, title_or_category: &str) -> f32 {
+        let query = title_or_category.trim().to_lowercase();
+        let starts: Vec<f32> = self.live_activities()
+            .filter(|a| a.title.trim().to_lowercase() == query || a.category == title_or_category)
+            .filter_map(|a| a.start_minutes())
+            .map(|minutes| minutes as f32)
+            .collect();
+        
+        if starts.is_empty() {
+            return 0.0;
+        }
+        
+        let mean = starts.iter().sum::<f32>() / starts.len() as f32;
+        let variance = starts.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / starts.len() as f32;
+        1.0 / (1.0 + variance.sqrt() / 60.0)
+    }
+    
+    // Aplicar as mesmas alterações a todas as atividades que satisfazem o filtro (tudo ou nada).
+    // Retorna quantas foram alteradas
+    pub fn edit_matching(&mut self, predicate: impl Fn(&Activity) -> bool, changes: ActivityPatch) -> Result<usize, String> {
+        let mut edited = self.activities.clone();
+        let mut ids = Vec::new();
+        for activity in edited.iter_mut().filter(|a| predicate(a)) {
+            changes.apply(activity);
+            activity.title = self.title_normalization.apply(&activity.title);
+            ids.push(activity.id.clone());
+        }
+        
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        
+        // Validar o resultado final antes de aplicar
+        for activity in edited.iter().filter(|a| ids.contains(&a.id)) {
+            self.validate_activity_in(activity, &edited)
+                .map_err(|e| format!("'{}' ({}): {}", activity.title, day_name(activity.day), e))?;
+        }
+        
+        self.record_undo();
+        self.activities = edited;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(ids.len())
+    }
+    
+    // Horas disponíveis na semana: a janela da grade nos 7 dias, sem os horários de silêncio
+    pub fn available_hours(&self) -> f32 {
+        let free_minutes_per_day = (self.start_hour * 60..self.end_hour * 60)
+            .filter(|minute| self.quiet_hours_overlap(*minute, minute + 1).is_none())
+            .count();
+        (free_minutes_per_day * WEEK_DAYS.len()) as f32 / 60.0
+    }
+    
+    // Porcentagem das horas disponíveis da semana que já está agendada
+    pub fn planned_percentage(&self) -> f32 {
+        self.calculate_weekly_stats().utilization_percent
+    }
+    
+    // Capturar o estado atual (atividades e configurações) para testar mudanças e voltar depois.
+    // Não grava nada em disco; as regras de add_validator não fazem parte da cópia
+    pub fn snapshot(&self) -> OrganizerSnapshot {
+        OrganizerSnapshot {
+            activities: self.activities.clone(),
+            categories: self.categories.clone(),
+            category_keywords: self.category_keywords.clone(),
+            quiet_hours: self.quiet_hours.clone(),
+            waitlist: self.waitlist.clone(),
+            goals: self.goals.clone(),
+            stats_precision: self.stats_precision,
+            fallback_color: self.fallback_color.clone(),
+            grid_collision_display: self.grid_collision_display,
+            title_normalization: self.title_normalization,
+            working_hours: (self.start_hour, self.end_hour),
+            slot_minutes: self.slot_minutes,
+            align_to_slots: self.align_to_slots,
+            tentative_blocks: self.tentative_blocks,
+            active_schedule: self.active_schedule.clone(),
+            schedules: self.schedules.clone(),
+        }
+    }
+    
+    // Voltar ao estado capturado por snapshot. Também não grava: o estado restaurado
+    // fica como alteração não salva até o próximo save_data (ou alteração com autosave)
+    pub fn restore(&mut self, snapshot: OrganizerSnapshot) {
+        self.activities = snapshot.activities;
+        self.invalidate_day_index();
+        self.clear_history();
+        self.categories = snapshot.categories;
+        self.category_keywords = snapshot.category_keywords;
+        self.quiet_hours = snapshot.quiet_hours;
+        self.waitlist = snapshot.waitlist;
+        self.goals = snapshot.goals;
+        self.stats_precision = snapshot.stats_precision;
+        self.fallback_color = snapshot.fallback_color;
+        self.grid_collision_display = snapshot.grid_collision_display;
+        self.title_normalization = snapshot.title_normalization;
+        (self.start_hour, self.end_hour) = snapshot.working_hours;
+        self.slot_minutes = snapshot.slot_minutes;
+        self.align_to_slots = snapshot.align_to_slots;
+        self.tentative_blocks = snapshot.tentative_blocks;
+        self.active_schedule = snapshot.active_schedule;
+        self.schedules = snapshot.schedules;
+        self.dirty.set(true);
+    }
+    
+    // Encaixar atividades sem horário fixo nos slots livres segundo a estratégia (tudo ou nada).
+    // Retorna os IDs criados, na ordem dos itens
+    pub fn auto_schedule(&mut self, items: Vec<FlexibleActivity>, strategy: PackStrategy) -> Result<Vec<String>, String> {
+        // Nada a encaixar: sem passo de desfazer nem alteração a salvar
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let mut pool = self.activities.clone();
+        let mut ids = Vec::new();
+        
+        for item in items {
+            let days: Vec<Weekday> = if item.days.is_empty() {
+                WEEKDAYS.to_vec()
+            } else {
+                item.days.clone()
+            };
+            
+            let mut activity = Activity {
+                id: Uuid::new_v4().to_string(),
+                title: self.title_normalization.apply(&item.title),
+                category: item.category.clone(),
+                duration: item.duration,
+                start_time: String::new(),
+                location: item.location.clone(),
+                description: item.description.clone(),
+                day: days[0],
+                created_at: Local::now(),
+                notes: Vec::new(),
+                effort: None,
+                depends_on: None,
+                status: ActivityStatus::default(),
+                series_id: None,
+                recurrence: None,
+                priority: Priority::default(),
+                tags: Vec::new(),
+                reminder_minutes: None,
+            };
+            
+            // Candidatos (dia, início) que passam em todas as validações
+            let mut candidates: Vec<(Weekday, i32)> = Vec::new();
+            for &day in &days {
+                for slot in self.free_slots_in(&pool, day, item.duration) {
+                    activity.day = day;
+                    activity.start_time = slot;
+                    if self.validate_activity_in(&activity, &pool).is_ok() {
+                        candidates.push((day, activity.start_minutes().unwrap()));
+                    }
+                }
+            }
+            
+            let day_minutes = |day: Weekday| -> i32 {
+                pool.iter().filter(|a| a.day == day).map(|a| duration_minutes(a.duration)).sum()
+            };
+            let edge_distance = |day: Weekday, start: i32| -> i32 {
+                let end = start + duration_minutes(item.duration);
+                pool.iter()
+                    .filter(|a| a.day == day)
+                    .filter_map(|a| Some((a.start_minutes()?, a.end_minutes()?)))
+                    .map(|(other_start, other_end)| (start - other_end).abs().min((other_start - end).abs()))
+                    .min()
+                    .unwrap_or(i32::MAX)
+            };
+            
+            // Os candidatos já estão na ordem da semana e do dia; min_by_key mantém o primeiro em empates
+            let chosen = match strategy {
+                PackStrategy::EarliestFit => candidates.first().cloned(),
+                PackStrategy::SpreadEvenly => candidates.iter().min_by_key(|(day, _)| day_minutes(*day)).cloned(),
+                PackStrategy::PackTight => candidates.iter().min_by_key(|(day, start)| edge_distance(*day, *start)).cloned(),
+            };
+            
+            let (day, start) = chosen
+                .ok_or_else(|| format!("'{}' não coube em nenhum horário livre", item.title))?;
+            activity.day = day;
+            activity.start_time = format_minutes(start);
+            ids.push(activity.id.clone());
+            pool.push(activity);
+        }
+        
+        self.record_undo();
+        self.activities = pool;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(ids)
+    }
+    
+    // Equilíbrio do tempo entre categorias: entropia de Shannon normalizada (0 = uma só
+    // categoria domina, 1 = tempo igual em todas as categorias existentes)
+    pub fn category_diversity(&self) -> f32 {
+        let stats = self.calculate_weekly_stats_minutes();
+        if stats.total_minutes == 0 || self.categories.len() < 2 {
+            return 0.0;
+        }
+        
+        let total = stats.total_minutes as f32;
+        let entropy: f32 = stats.by_category.values()
+            .filter(|minutes| **minutes > 0)
+            .map(|minutes| {
+                let p = *minutes as f32 / total;
+                -p * p.ln()
+            })
+            .sum();
+        
+        entropy / (self.categories.len() as f32).ln()
+    }
+    
+    // Minutos livres antes e depois da atividade no seu dia (None se não houver vizinha
+    // daquele lado, se estiver colada a ela ou se a atividade não existir)
+    pub fn neighbor_gaps(&self, id: &str) -> (Option<u32>, Option<u32>) {
+        let activity = match self.get_activity(id) {
+            Some(activity) => activity,
+            None => return (None, None),
+        };
+        let (start, end) = match (activity.start_minutes(), activity.end_minutes()) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return (None, None),
+        };
+        
+        let neighbors: Vec<(i32, i32)> = self.live_activities()
+            .filter(|a| a.day == activity.day && a.id != activity.id)
+            .filter_map(|a| Some((a.start_minutes()?, a.end_minutes()?)))
+            .collect();
+        
+        let before = neighbors.iter()
+            .filter(|(other_start, _)| *other_start < start)
+            .map(|(_, other_end)| *other_end)
+            .max()
+            .map(|previous_end| start - previous_end)
+            .filter(|gap| *gap > 0)
+            .map(|gap| gap as u32);
+        let after = neighbors.iter()
+            .filter(|(other_start, _)| *other_start > start)
+            .map(|(other_start, _)| *other_start)
+            .min()
+            .map(|next_start| next_start - end)
+            .filter(|gap| *gap > 0)
+            .map(|gap| gap as u32);
+        
+        (before, after)
+    }
+    
+    // Indica se a atividade ocupa o horário para fins de conflito
+    fn blocks_time(&self, activity: &Activity) -> bool {
+        match activity.status {
+            ActivityStatus::Confirmed => true,
+            ActivityStatus::Tentative => self.tentative_blocks,
+            ActivityStatus::Cancelled => false,
+        }
+    }
+    
+    // Definir se atividades provisórias bloqueiam o horário (padrão) ou podem ser sobrepostas
+    pub fn set_tentative_blocks(&mut self, blocks: bool) {
+        self.tentative_blocks = blocks;
+    }
+    
+    // Alterar a situação de uma atividade (reativar uma cancelada volta a checar conflitos)
+    pub fn set_status(&mut self, id: &str, status: ActivityStatus) -> Result<(), String> {
+        let changes = ActivityPatch { status: Some(status), ..ActivityPatch::default() };
+        Ok(self.edit_activity_with(id, changes)?)
+    }
+    
+    // Alterar a prioridade de uma atividade
+    pub fn set_priority(&mut self, id: &str, priority: Priority) -> Result<(), String> {
+        let changes = ActivityPatch { priority: Some(priority), ..ActivityPatch::default() };
+        Ok(self.edit_activity_with(id, changes)?)
+    }
+    
+    // Substituir as tags de uma atividade
+    pub fn set_tags(&mut self, id: &str, tags: &[String]) -> Result<(), String> {
+        let changes = ActivityPatch { tags: Some(tags.to_vec()), ..ActivityPatch::default() };
+        Ok(self.edit_activity_with(id, changes)?)
+    }
+    
+    // Definir (ou remover) o lembrete de uma atividade, em minutos antes do início
+    pub fn set_reminder(&mut self, id: &str, minutes_before: Option<u32>) -> Result<(), String> {
+        let changes = ActivityPatch { reminder_minutes: Some(minutes_before), ..ActivityPatch::default() };
+        Ok(self.edit_activity_with(id, changes)?)
+    }
+    
+    // Atividades cujo lembrete (início na semana de `now` menos a antecedência) cai no
+    // próximo minuto. Feito para ser consultado a cada minuto; lembretes que caem na semana
+    // anterior (ex.: Segunda 00:10 com 30 min) são tratados com volta cíclica
+    pub fn upcoming_reminders(&self, now: DateTime<Local>) -> Vec<&Activity> {
+        const WEEK_SECONDS: i64 = 7 * 24 * 60 * 60;
+        let now_seconds = now.weekday().num_days_from_monday() as i64 * 24 * 60 * 60
+            + now.num_seconds_from_midnight() as i64;
+        
+        self.live_activities()
+            .filter(|a| {
+                let (lead, start) = match (a.reminder_minutes, a.start_minutes()) {
+                    (Some(lead), Some(start)) => (lead as i64, start as i64),
+                    _ => return false,
+                };
+                let day = a.day.num_days_from_monday() as i64 * 24 * 60;
+                let reminder_seconds = (day + start - lead) * 60;
+                (reminder_seconds - now_seconds).rem_euclid(WEEK_SECONDS) < 60
+            })
+            .collect()
+    }
+    
+    // Atividades que valem para a agenda (todas menos as canceladas): base de listagens,
+    // buscas e estatísticas
+    fn live_activities(&self) -> impl Iterator<Item = &Activity> {
+        self.activities.iter().filter(|a| a.status != ActivityStatus::Cancelled)
+    }
+    
+    // Atividades canceladas (ocultas nas listagens, mas mantidas)
+    pub fn cancelled_activities(&self) -> Vec<&Activity> {
+        self.activities.iter()
+            .filter(|a| a.status == ActivityStatus::Cancelled)
+            .collect()
+    }
+    
+    // Início da primeira e término da última atividade do dia ("HH:MM"), se houver alguma
+    pub fn day_bounds(&self, day: &str) -> Option<(String, String)> {
+        let activities = self.get_activities_by_day(day);
+        let first = activities.iter().filter_map(|a| a.start_minutes()).min()?;
+        let last = activities.iter().filter_map(|a| a.end_minutes()).max()?;
+        Some((format_minutes(first), format_minutes(last % (24 * 60))))
+    }
+    
+    // Intervalos livres do dia dentro do horário de trabalho: (início, fim, duração em horas),
+    // incluindo o trecho antes da primeira e o depois da última atividade
+    pub fn find_gaps(&self, day: &str) -> Vec<(String, String, f32)> {
+        let weekday = match self.resolve_day(day) {
+            Ok(weekday) => weekday,
+            Err(_) => return Vec::new(),
+        };
+        let (day_start, day_end) = (self.start_hour * 60, self.end_hour * 60);
+        
+        // O que passa da meia-noite do dia anterior ocupa o começo deste dia
+        let mut cursor = self.get_activities_by_day(day_name(weekday.pred())).iter()
+            .filter_map(|a| a.end_minutes())
+            .map(|end| end - 24 * 60)
+            .fold(day_start, i32::max);
+        
+        let mut gaps = Vec::new();
+        let mut push_gap = |from: i32, to: i32| {
+            if from < to {
+                gaps.push((format_minutes(from), format_minutes(to), (to - from) as f32 / 60.0));
+            }
+        };
+        for activity in self.get_activities_by_day(day) {
+            let (start, end) = match (activity.start_minutes(), activity.end_minutes()) {
+                (Some(start), Some(end)) => (start, end),
+                _ => continue,
+            };
+            push_gap(cursor, start.min(day_end));
+            cursor = cursor.max(end);
+        }
+        push_gap(cursor, day_end);
+        gaps
+    }
+    
+    // Horário para começar a se preparar em cada dia com atividades: início da primeira
+    // atividade menos `prep_minutes` (pode cair na noite anterior)
+    pub fn prep_time_warnings(&self, prep_minutes: u32) -> HashMap<String, String> {
+        WEEK_DAYS.iter()
+            .filter_map(|day| {
+                let (first, _) = self.day_bounds(day)?;
+                let prep = (parse_minutes(&first)? - prep_minutes as i32).rem_euclid(24 * 60);
+                Some((day.to_string(), format_minutes(prep)))
+            })
+            .collect()
+    }
+    
+    // Exportar um arquivo por categoria com atividades (ex.: "<dir>/trabalho.csv"),
+    // retornando os caminhos criados
+    pub fn export_by_category(&self, dir: &str, format: ExportFormat) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        fs::create_dir_all(dir)?;
+        
+        let mut keys: Vec<&String> = self.categories.keys()
+            .filter(|key| self.activities.iter().any(|a| a.category == **key))
+            .collect();
+        keys.sort();
+        
+        let mut paths = Vec::new();
+        for key in keys {
+            let path = Path::new(dir).join(format!("{}.{}", key, format.extension()));
+            let filename = path.to_string_lossy().to_string();
+            match format {
+                ExportFormat::Csv => self.export_to_csv_filtered(&filename, |a| a.category == *key)?,
+                ExportFormat::Markdown => self.export_to_markdown_filtered(&filename, |a| a.category == *key)?,
+            }
+            paths.push(filename);
+        }
+        
+        Ok(paths)
+    }
+    
+    // Categorias sem nenhuma atividade (nem cancelada) em nenhuma agenda e fora da lista de espera
+    pub fn unused_categories(&self) -> Vec<&String> {
+        let mut unused: Vec<&String> = self.categories.keys()
+            .filter(|key| self.category_usage(key) == 0)
+            .collect();
+        unused.sort();
+        unused
+    }
+    
+    // Quantas atividades (de todas as agendas, já que as categorias são compartilhadas)
+    // e itens da lista de espera usam a categoria
+    fn category_usage(&self, key: &str) -> usize {
+        self.activities.iter()
+            .chain(self.schedules.values().flatten())
+            .filter(|a| a.category == key)
+            .count()
+            + self.waitlist.iter().filter(|p| p.category == key).count()
+    }
+    
+    // Remover as categorias não usadas (com suas metas e palavras-chave), retornando as chaves removidas
+    pub fn remove_unused_categories(&mut self) -> Vec<String> {
+        let removed: Vec<String> = self.unused_categories().into_iter().cloned().collect();
+        if removed.is_empty() {
+            return removed;
+        }
+        
+        self.clear_history();
+        for key in &removed {
+            self.categories.remove(key);
+            self.goals.remove(key);
+        }
+        self.category_keywords.retain(|(_, category)| !removed.contains(category));
+        
+        // Salvar automaticamente
+        self.persist();
+        removed
+    }
+    
+    // Próximo (dia, horário) a partir de `from` em que cabe um bloco da duração informada.
+    // Procura do resto de hoje até o mesmo dia da semana seguinte; None se nada couber
+    pub fn next_free_block(&self, from: DateTime<Local>, duration: f32) -> Option<(String, String)> {
+        let today = from.weekday().num_days_from_monday() as usize;
+        let now = (from.hour() * 60 + from.minute()) as i32;
+        
+        for offset in 0..=WEEK_DAYS.len() {
+            let day = WEEK_DAYS[(today + offset) % WEEK_DAYS.len()];
+            let slot = self.find_free_slots(day, duration).into_iter().find(|slot| {
+                let start = parse_minutes(slot).unwrap();
+                match offset {
+                    0 => start >= now,
+                    7 => start < now,
+                    _ => true,
+                }
+            });
+            if let Some(slot) = slot {
+                return Some((day.to_string(), slot));
+            }
+        }
         
-        // Categorias padrão
-        organizer.init_default_categories();
+        None
+    }
+    
+    // Criar uma atividade recorrente: uma ocorrência por dia da regra, todas com o mesmo
+    // series_id (tudo ou nada). Retorna os IDs criados, na ordem da semana
+    pub fn add_recurring_activity(&mut self, params: &ActivityParams, recurrence: Recurrence) -> Result<Vec<String>, String> {
+        let mut days = recurrence.days(params.day);
+        if days.is_empty() {
+            return Err("A recorrência precisa de pelo menos um dia".to_string());
+        }
+        if let Recurrence::EveryNWeeks(0) = recurrence {
+            return Err("O intervalo de semanas deve ser maior que zero".to_string());
+        }
+        days.sort_by_key(|d| d.num_days_from_monday());
+        days.dedup();
         
-        // Carregar dados existentes
-        if let Err(e) = organizer.load_data() {
-            println!("Aviso: Não foi possível carregar dados existentes: {}", e);
-            println!("Iniciando com dados limpos.");
+        let series_id = Uuid::new_v4().to_string();
+        let mut pool = self.activities.clone();
+        let mut ids = Vec::new();
+        for day in days {
+            let mut activity = params.to_activity();
+            activity.title = self.title_normalization.apply(&activity.title);
+            activity.day = day;
+            activity.series_id = Some(series_id.clone());
+            activity.recurrence = Some(recurrence.clone());
+            
+            // Cada ocorrência é validada contra a agenda e as ocorrências anteriores
+            self.validate_activity_in(&activity, &pool)
+                .map_err(|e| format!("{}: {}", day_name(activity.day), e))?;
+            ids.push(activity.id.clone());
+            pool.push(activity);
         }
         
-        organizer
+        self.record_undo();
+        self.activities = pool;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(ids)
     }
     
-    // Inicializar categorias padrão
-    fn init_default_categories(&mut self) {
-        let default_categories = [
-            ("trabalho", Category { name: "Trabalho".to_string(), color: "#3B82F6".to_string() }),
-            ("pessoal", Category { name: "Pessoal".to_string(), color: "#10B981".to_string() }),
-            ("saude", Category { name: "Saúde".to_string(), color: "#F59E0B".to_string() }),
-            ("estudo", Category { name: "Estudo".to_string(), color: "#8B5CF6".to_string() }),
-            ("lazer", Category { name: "Lazer".to_string(), color: "#EF4444".to_string() }),
-            ("reuniao", Category { name: "Reunião".to_string(), color: "#F97316".to_string() }),
-            ("exercicio", Category { name: "Exercício".to_string(), color: "#06B6D4".to_string() }),
-        ];
+    // Ocorrências de uma série, na ordem da semana
+    pub fn series_occurrences(&self, series_id: &str) -> Vec<&Activity> {
+        self.get_all_activities().into_iter()
+            .filter(|a| a.series_id.as_deref() == Some(series_id))
+            .collect()
+    }
+    
+    // Desvincular uma ocorrência da série: ela passa a ser uma atividade avulsa e não é
+    // mais afetada por edit_series/remove_series
+    pub fn detach_from_series(&mut self, id: &str) -> Result<(), String> {
+        let activity = self.get_activity(id)
+            .ok_or("Atividade não encontrada")?;
+        if activity.series_id.is_none() {
+            return Err("A atividade não faz parte de uma série".to_string());
+        }
         
-        for (key, category) in default_categories {
-            self.categories.insert(key.to_string(), category);
+        self.record_undo();
+        let activity = self.get_activity_mut(id).unwrap();
+        activity.series_id = None;
+        activity.recurrence = None;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Aplicar as mesmas alterações a todas as ocorrências de uma série (tudo ou nada)
+    pub fn edit_series(&mut self, series_id: &str, changes: ActivityPatch) -> Result<usize, String> {
+        let edited = self.edit_matching(|a| a.series_id.as_deref() == Some(series_id), changes)?;
+        if edited == 0 {
+            return Err("Série não encontrada".to_string());
         }
+        Ok(edited)
     }
     
-    // Gerar horários de 30 em 30 minutos
-    pub fn generate_time_slots() -> Vec<String> {
-        let mut slots = Vec::new();
-        for hour in 6..23 { // 6h às 22h30
-            slots.push(format!("{:02}:00", hour));
-            slots.push(format!("{:02}:30", hour));
+    // Remover todas as ocorrências de uma série, retornando quantas foram removidas e os IDs
+    // agendados da lista de espera no espaço liberado
+    pub fn remove_series(&mut self, series_id: &str) -> Result<(usize, Vec<String>), String> {
+        let ids: Vec<String> = self.activities.iter()
+            .filter(|a| a.series_id.as_deref() == Some(series_id))
+            .map(|a| a.id.clone())
+            .collect();
+        if ids.is_empty() {
+            return Err("Série não encontrada".to_string());
         }
-        slots
+        
+        // Um único passo de desfazer e um único salvamento para a série inteira
+        self.record_undo();
+        let scheduled = self.remove_ids(&ids);
+        Ok((ids.len(), scheduled))
     }
     
-    // Validar horário
-    fn validate_time(&self, time: &str) -> Result<(), String> {
-        if NaiveTime::parse_from_str(time, "%H:%M").is_err() {
-            return Err(format!("Horário inválido: {}", time));
+    // Categorias cadastradas (chave → categoria)
+    pub fn categories(&self) -> &HashMap<String, Category> {
+        &self.categories
+    }
+    
+    // Criar uma categoria nova; a chave deve ser única e a cor no formato #RRGGBB
+    pub fn add_category(&mut self, key: &str, name: &str, color: &str) -> Result<(), String> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err("A chave da categoria não pode estar vazia".to_string());
+        }
+        if self.categories.contains_key(key) {
+            return Err(format!("Categoria '{}' já existe", key));
+        }
+        if name.trim().is_empty() {
+            return Err("O nome da categoria não pode estar vazio".to_string());
+        }
+        if parse_hex_color(color).is_none() {
+            return Err(format!("Cor inválida: {}. Use o formato #RRGGBB", color));
         }
+        
+        self.categories.insert(key.to_string(), Category { name: name.trim().to_string(), color: color.to_string() });
+        
+        // Salvar automaticamente
+        self.persist();
         Ok(())
     }
     
-    // Validar dia da semana
-    fn validate_day(&self, day: &str) -> Result<(), String> {
-        let valid_days = ["Segunda", "Terça", "Quarta", "Quinta", "Sexta", "Sábado", "Domingo"];
-        if !valid_days.contains(&day) {
-            return Err(format!("Dia inválido: {}. Use: {}", day, valid_days.join(", ")));
+    // Alterar nome e/ou cor de uma categoria (a chave não muda)
+    pub fn edit_category(&mut self, key: &str, name: Option<&str>, color: Option<&str>) -> Result<(), String> {
+        if !self.categories.contains_key(key) {
+            return Err(format!("Categoria '{}' não existe", key));
+        }
+        if let Some(name) = name {
+            if name.trim().is_empty() {
+                return Err("O nome da categoria não pode estar vazio".to_string());
+            }
+        }
+        if let Some(color) = color {
+            if parse_hex_color(color).is_none() {
+                return Err(format!("Cor inválida: {}. Use o formato #RRGGBB", color));
+            }
         }
+        
+        let category = self.categories.get_mut(key).unwrap();
+        if let Some(name) = name { category.name = name.trim().to_string(); }
+        if let Some(color) = color { category.color = color.to_string(); }
+        
+        // Salvar automaticamente
+        self.persist();
         Ok(())
     }
     
-    // Verificar conflito de horários
-    fn check_time_conflict(&self, day: &str, start_time: &str, duration: f32) -> Option<&Activity> {
-        let start = NaiveTime::parse_from_str(start_time, "%H:%M").unwrap();
-        let end_minutes = start.hour() as i32 * 60 + start.minute() as i32 + (duration * 60.0) as i32;
-        let end_hour = end_minutes / 60;
-        let end_min = end_minutes % 60;
+    // Remover uma categoria sem atividades (nem na lista de espera)
+    pub fn remove_category(&mut self, key: &str) -> Result<(), String> {
+        if !self.categories.contains_key(key) {
+            return Err(format!("Categoria '{}' não existe", key));
+        }
+        let in_use = self.category_usage(key);
+        if in_use > 0 {
+            return Err(format!("Categoria '{}' ainda é usada por {} atividade(s)", key, in_use));
+        }
         
-        for activity in &self.activities {
-            if activity.day == day {
-                let activity_start = NaiveTime::parse_from_str(&activity.start_time, "%H:%M").unwrap();
-                let activity_end_minutes = activity_start.hour() as i32 * 60 + activity_start.minute() as i32 + (activity.duration * 60.0) as i32;
-                
-                let start_minutes = start.hour() as i32 * 60 + start.minute() as i32;
-                
-                // Verificar sobreposição
-                if (start_minutes < activity_end_minutes) && (end_minutes > activity_start.hour() as i32 * 60 + activity_start.minute() as i32) {
-                    return Some(activity);
-                }
+        self.clear_history();
+        self.categories.remove(key);
+        self.goals.remove(key);
+        self.category_keywords.retain(|(_, category)| category != key);
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Remover uma categoria passando suas atividades (e palavras-chave) para `fallback` (tudo ou nada).
+    // Retorna quantas atividades, somando todas as agendas, foram reatribuídas
+    pub fn remove_category_reassigning(&mut self, key: &str, fallback: &str) -> Result<usize, String> {
+        if !self.categories.contains_key(key) {
+            return Err(format!("Categoria '{}' não existe", key));
+        }
+        if key == fallback || !self.categories.contains_key(fallback) {
+            return Err(format!("Categoria de destino inválida: {}", fallback));
+        }
+        
+        // A agenda ativa e as demais, já que as categorias valem para todas
+        let mut reassigned = self.activities.clone();
+        let mut other_schedules = self.schedules.clone();
+        let mut moved = 0;
+        for pool in std::iter::once(&mut reassigned).chain(other_schedules.values_mut()) {
+            for activity in pool.iter_mut().filter(|a| a.category == key) {
+                activity.category = fallback.to_string();
+                moved += 1;
             }
         }
-        None
+        
+        // Validar o resultado final antes de aplicar (regras personalizadas podem depender da categoria)
+        for pool in std::iter::once(&reassigned).chain(other_schedules.values()) {
+            for activity in pool.iter().filter(|a| a.category == fallback) {
+                self.validate_activity_in(activity, pool)
+                    .map_err(|e| format!("'{}' ({}): {}", activity.title, day_name(activity.day), e))?;
+            }
+        }
+        
+        // O histórico guarda atividades com a categoria removida; desfazer não a traria de volta
+        self.clear_history();
+        self.activities = reassigned;
+        self.schedules = other_schedules;
+        for params in self.waitlist.iter_mut().filter(|p| p.category == key) {
+            params.category = fallback.to_string();
+        }
+        for (_, category) in self.category_keywords.iter_mut().filter(|(_, c)| c == key) {
+            *category = fallback.to_string();
+        }
+        self.categories.remove(key);
+        self.goals.remove(key);
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(moved)
     }
     
-    // Adicionar nova atividade
-    pub fn add_activity(&mut self, title: &str, category: &str, day: &str, start_time: &str, duration: f32, location: Option<String>, description: Option<String>) -> Result<String, String> {
-        // Validações
-        self.validate_day(day)?;
-        self.validate_time(start_time)?;
+    // Exigir (padrão) ou não que os horários de início caiam em um slot da grade. Desativar permite
+    // horários livres, mas a grade semanal só exibe atividades que começam em um slot
+    pub fn set_slot_alignment(&mut self, enabled: bool) {
+        self.align_to_slots = enabled;
+    }
+    
+    // Importar atividades de um CSV no formato de export_to_csv. Registros inválidos ou em
+    // conflito são pulados e voltam no relatório com o motivo; se nenhum for importado, a
+    // agenda fica como estava (mesmo em ImportMode::Replace)
+    pub fn import_from_csv(&mut self, filename: &str, mode: ImportMode) -> Result<ImportReport, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(filename)?;
         
-        if !self.categories.contains_key(category) {
-            return Err(format!("Categoria '{}' não existe", category));
-        }
+        let mut pool = match mode {
+            ImportMode::Append => self.activities.clone(),
+            ImportMode::Replace => Vec::new(),
+        };
+        let mut report = ImportReport::default();
         
-        if duration <= 0.0 || duration > 8.0 {
-            return Err("Duração deve ser entre 0.5 e 8 horas".to_string());
+        // O primeiro registro é o cabeçalho
+        for (index, fields) in parse_csv(&content).iter().enumerate().skip(1) {
+            if fields.iter().all(|f| f.trim().is_empty()) {
+                continue;
+            }
+            
+            if fields.len() != 9 {
+                report.rejected.push((index + 1, format!("esperadas 9 colunas, encontradas {}", fields.len())));
+                continue;
+            }
+            
+            let duration: f32 = match fields[5].trim().parse() {
+                Ok(duration) => duration,
+                Err(_) => {
+                    report.rejected.push((index + 1, format!("duração inválida: {}", fields[5])));
+                    continue;
+                }
+            };
+            let optional = |field: &String| if field.is_empty() { None } else { Some(field.clone()) };
+            let day = match self.resolve_day(&fields[3]) {
+                Ok(day) => day,
+                Err(_) => {
+                    report.rejected.push((index + 1, format!("dia inválido: {}", fields[3])));
+                    continue;
+                }
+            };
+            let created_at = chrono::NaiveDateTime::parse_from_str(fields[8].trim(), "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .and_then(|naive| naive.and_local_timezone(Local).single())
+                .unwrap_or_else(Local::now);
+            
+            // IDs vazios ou repetidos ganham um novo
+            let id = match fields[0].trim() {
+                "" => Uuid::new_v4().to_string(),
+                id if pool.iter().any(|a| a.id == id) => Uuid::new_v4().to_string(),
+                id => id.to_string(),
+            };
+            
+            let activity = Activity {
+                id,
+                title: self.title_normalization.apply(&fields[1]),
+                category: fields[2].trim().to_string(),
+                duration,
+                start_time: fields[4].trim().to_string(),
+                location: optional(&fields[6]),
+                description: optional(&fields[7]),
+                day,
+                created_at,
+                notes: Vec::new(),
+                effort: None,
+                depends_on: None,
+                status: ActivityStatus::default(),
+                series_id: None,
+                recurrence: None,
+                priority: Priority::default(),
+                tags: Vec::new(),
+                reminder_minutes: None,
+            };
+            
+            match self.validate_activity_in(&activity, &pool) {
+                Ok(()) => {
+                    pool.push(activity);
+                    report.imported += 1;
+                }
+                Err(e) => report.rejected.push((index + 1, format!("'{}': {}", activity.title, e))),
+            }
         }
         
-        if title.trim().is_empty() {
-            return Err("Título não pode estar vazio".to_string());
+        // Arquivo vazio ou todo rejeitado não pode apagar a agenda atual
+        if report.imported == 0 {
+            return Ok(report);
         }
         
-        // Verificar conflitos
-        if let Some(conflicting_activity) = self.check_time_conflict(day, start_time, duration) {
-            return Err(format!("Conflito de horário com: '{}'", conflicting_activity.title));
+        self.record_undo();
+        self.activities = pool;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(report)
+    }
+    
+    // Percorrer todas as atividades na ordem em que estão guardadas (inclui canceladas),
+    // sem montar um Vec como get_all_activities
+    pub fn activities(&self) -> impl Iterator<Item = &Activity> {
+        self.activities.iter()
+    }
+    
+    // Percorrer todas as atividades para alteração direta (sem validação nem salvamento automático)
+    pub fn activities_mut(&mut self) -> impl Iterator<Item = &mut Activity> {
+        self.invalidate_day_index();
+        self.activities.iter_mut()
+    }
+    
+    // Buscar uma atividade pelo ID
+    pub fn get_activity(&self, id: &str) -> Option<&Activity> {
+        self.activities.iter().find(|a| a.id == id)
+    }
+    
+    // Buscar uma atividade pelo ID para alteração direta (sem validação nem salvamento automático)
+    pub fn get_activity_mut(&mut self, id: &str) -> Option<&mut Activity> {
+        self.invalidate_day_index();
+        self.activities.iter_mut().find(|a| a.id == id)
+    }
+    
+    // Mudar uma atividade de dia e horário (conflitos ignoram a própria atividade)
+    pub fn move_activity(&mut self, id: &str, new_day: &str, new_start_time: &str) -> Result<(), WeeklyError> {
+        self.edit_activity(id, None, None, Some(new_day), Some(new_start_time), None, None, None)
+    }
+    
+    // Copiar uma atividade (novo ID e data de criação), opcionalmente para outro dia/horário.
+    // A cópia é avulsa: não faz parte da série da original. Retorna o ID da cópia
+    pub fn duplicate_activity(&mut self, id: &str, new_day: Option<&str>, new_start_time: Option<&str>) -> Result<String, WeeklyError> {
+        let mut copy = self.get_activity(id)
+            .ok_or(WeeklyError::ActivityNotFound)?
+            .clone();
+        copy.id = Uuid::new_v4().to_string();
+        copy.created_at = Local::now();
+        copy.series_id = None;
+        copy.recurrence = None;
+        if let Some(day) = new_day { copy.day = self.resolve_day(day)?; }
+        if let Some(start_time) = new_start_time { copy.start_time = start_time.to_string(); }
+        
+        self.insert_activity(copy)
+    }
+    
+    // Definir a janela diária da grade (horas cheias, ex.: 5 e 21 para 05:00–20:30).
+    // Atividades fora da nova janela continuam salvas, mas somem da grade
+    pub fn set_working_hours(&mut self, start_hour: i32, end_hour: i32) -> Result<(), String> {
+        check_working_hours(start_hour, end_hour)?;
+        
+        self.start_hour = start_hour;
+        self.end_hour = end_hour;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Janela diária atual da grade (start_hour, end_hour)
+    pub fn working_hours(&self) -> (i32, i32) {
+        (self.start_hour, self.end_hour)
+    }
+    
+    // Definir a granularidade da grade (15, 30 ou 60 minutos). Vale para os slots, o
+    // alinhamento dos horários de início e o múltiplo exigido na duração. Atividades já
+    // salvas que não se alinham à nova granularidade (ex.: 09:15 ao passar para 30) não
+    // são alteradas: continuam salvas e contam nos conflitos, mas somem da grade e só
+    // passam na validação de uma edição se forem ajustadas
+    pub fn set_slot_minutes(&mut self, slot_minutes: u32) -> Result<(), String> {
+        check_slot_minutes(slot_minutes)?;
+        
+        self.slot_minutes = slot_minutes;
+        
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Nome da agenda ativa (a que todos os demais métodos consultam e alteram)
+    pub fn active_schedule(&self) -> &str {
+        &self.active_schedule
+    }
+    
+    // Nomes de todas as agendas, inclusive a ativa, em ordem alfabética
+    pub fn schedule_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.schedules.keys().cloned().collect();
+        names.push(self.active_schedule.clone());
+        names.sort();
+        names
+    }
+    
+    // Criar uma agenda vazia (não muda a ativa)
+    pub fn create_schedule(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Nome da agenda não pode estar vazio".to_string());
+        }
+        if name == self.active_schedule || self.schedules.contains_key(name) {
+            return Err(format!("Agenda '{}' já existe", name));
         }
         
-        // Criar atividade
-        let activity = Activity {
-            id: Uuid::new_v4().to_string(),
-            title: title.to_string(),
-            category: category.to_string(),
-            duration,
-            start_time: start_time.to_string(),
-            location,
-            description,
-            day: day.to_string(),
-            created_at: Local::now(),
-        };
+        self.schedules.insert(name.to_string(), Vec::new());
         
-        let id = activity.id.clone();
-        self.activities.push(activity);
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
+    }
+    
+    // Tornar ativa outra agenda. Categorias, metas, lista de espera e configurações são
+    // compartilhadas; só as atividades mudam. O histórico de desfazer é da agenda anterior
+    // e é descartado
+    pub fn switch_schedule(&mut self, name: &str) -> Result<(), String> {
+        if name == self.active_schedule {
+            return Ok(());
+        }
+        let activities = self.schedules.remove(name)
+            .ok_or_else(|| format!("Agenda '{}' não encontrada", name))?;
+        
+        let previous = std::mem::replace(&mut self.active_schedule, name.to_string());
+        self.schedules.insert(previous, std::mem::replace(&mut self.activities, activities));
+        self.clear_history();
         
         // Salvar automaticamente
-        if let Err(e) = self.save_data() {
-            println!("Aviso: Erro ao salvar dados: {}", e);
+        self.persist();
+        Ok(())
+    }
+    
+    // Excluir uma agenda e suas atividades (a ativa não pode ser excluída)
+    pub fn delete_schedule(&mut self, name: &str) -> Result<(), String> {
+        if name == self.active_schedule {
+            return Err("Não é possível excluir a agenda ativa. Troque de agenda antes".to_string());
         }
+        self.schedules.remove(name)
+            .ok_or_else(|| format!("Agenda '{}' não encontrada", name))?;
         
-        Ok(id)
+        // Salvar automaticamente
+        self.persist();
+        Ok(())
     }
     
-    // Editar atividade
-    pub fn edit_activity(&mut self, id: &str, title: Option<&str>, category: Option<&str>, day: Option<&str>, start_time: Option<&str>, duration: Option<f32>, location: Option<String>, description: Option<String>) -> Result<(), String> {
-        let activity = self.activities.iter_mut().find(|a| a.id == id)
-            .ok_or("Atividade não encontrada")?;
+    // Granularidade atual da grade, em minutos
+    pub fn slot_minutes(&self) -> u32 {
+        self.slot_minutes
+    }
+}
+
+// Construtor fluente de atividades (evita trocar a ordem de dia/horário/categoria)
+#[derive(Debug, Clone, Default)]
+pub struct ActivityBuilder {
+    title: String,
+    category: String,
+    day: String,
+    start_time: String,
+    duration: f32,
+    location: Option<String>,
+    description: Option<String>,
+    effort: Option<u8>,
+    depends_on: Option<String>,
+    status: ActivityStatus,
+    priority: Priority,
+    tags: Vec<String>,
+    reminder_minutes: Option<u32>,
+}
+
+impl ActivityBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+    
+    pub fn category(mut self, category: &str) -> Self {
+        self.category = category.to_string();
+        self
+    }
+    
+    pub fn day(mut self, day: &str) -> Self {
+        self.day = day.to_string();
+        self
+    }
+    
+    pub fn start_time(mut self, start_time: &str) -> Self {
+        self.start_time = start_time.to_string();
+        self
+    }
+    
+    pub fn duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+    
+    pub fn location(mut self, location: &str) -> Self {
+        self.location = Some(location.to_string());
+        self
+    }
+    
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+    
+    pub fn effort(mut self, effort: u8) -> Self {
+        self.effort = Some(effort);
+        self
+    }
+    
+    pub fn depends_on(mut self, activity_id: &str) -> Self {
+        self.depends_on = Some(activity_id.to_string());
+        self
+    }
+    
+    pub fn status(mut self, status: ActivityStatus) -> Self {
+        self.status = status;
+        self
+    }
+    
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+    
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+    
+    pub fn reminder(mut self, minutes_before: u32) -> Self {
+        self.reminder_minutes = Some(minutes_before);
+        self
+    }
+    
+    // Validar e inserir a atividade no organizador, retornando o ID
+    pub fn build(self, organizer: &mut WeeklyOrganizer) -> Result<String, WeeklyError> {
+        organizer.insert_activity(Activity {
+            id: Uuid::new_v4().to_string(),
+            title: self.title,
+            category: self.category,
+            duration: self.duration,
+            start_time: self.start_time,
+            location: self.location,
+            description: self.description,
+            day: organizer.resolve_day(&self.day)?,
+            created_at: Local::now(),
+            notes: Vec::new(),
+            effort: self.effort,
+            depends_on: self.depends_on,
+            status: self.status,
+            series_id: None,
+            recurrence: None,
+            priority: self.priority,
+            tags: normalize_tags(&self.tags),
+            reminder_minutes: self.reminder_minutes,
+        })
+    }
+}
+
+// Testes da biblioteca, logo depois dela; a CLI, que só repassa entrada e saída, vem em seguida
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+    use super::*;
+    
+    // Caminho único no diretório temporário (os testes rodam em paralelo)
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("organizador-{}-{}", Uuid::new_v4(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+    
+    // Organizador vazio (só com as categorias padrão) que não grava nada sozinho
+    fn organizer() -> WeeklyOrganizer {
+        let mut organizer = WeeklyOrganizer::open(&temp_path("dados.json")).unwrap();
+        organizer.set_autosave(false);
+        organizer
+    }
+    
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("Reunião"), "Reunião");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("Café, \"especial\"\nturno"), "\"Café, \"\"especial\"\"\nturno\"");
+    }
+    
+    #[test]
+    fn parse_csv_reads_quoted_fields() {
+        let content = "ID,Título\r\n1,\"Café, \"\"especial\"\"\nturno\"\r\n2,simples\r\n";
+        assert_eq!(parse_csv(content), vec![
+            vec!["ID".to_string(), "Título".to_string()],
+            vec!["1".to_string(), "Café, \"especial\"\nturno".to_string()],
+            vec!["2".to_string(), "simples".to_string()],
+        ]);
+    }
+    
+    #[test]
+    fn csv_export_import_round_trip() {
+        let mut source = organizer();
+        let title = "Café, \"especial\"\nturno";
+        let id = source.add_activity(title, "pessoal", "Terça", "09:00", 1.5, Some("Sala 2, térreo".to_string()), None).unwrap();
         
-        // Criar uma cópia para validação
-        let mut temp_activity = activity.clone();
+        let csv = temp_path("atividades.csv");
+        source.export_to_csv(&csv).unwrap();
+        let mut target = organizer();
+        let report = target.import_from_csv(&csv, ImportMode::Replace).unwrap();
+        fs::remove_file(&csv).unwrap();
+        assert_eq!(report.imported, 1);
+        assert!(report.rejected.is_empty());
         
-        // Aplicar mudanças temporariamente
-        if let Some(t) = title { temp_activity.title = t.to_string(); }
-        if let Some(c) = category { temp_activity.category = c.to_string(); }
-        if let Some(d) = day { temp_activity.day = d.to_string(); }
-        if let Some(st) = start_time { temp_activity.start_time = st.to_string(); }
-        if let Some(dur) = duration { temp_activity.duration = dur; }
+        let imported = target.get_activity(&id).unwrap();
+        assert_eq!(imported.title, title);
+        assert_eq!(imported.location.as_deref(), Some("Sala 2, térreo"));
+        assert_eq!(imported.description, None);
+        assert_eq!(imported.day, Weekday::Tue);
+        assert_eq!(imported.start_time, "09:00");
+        assert_eq!(imported.duration, 1.5);
+    }
+    
+    #[test]
+    fn csv_import_reports_rejected_records() {
+        let csv = temp_path("importar.csv");
+        fs::write(&csv, "\
+ID,Título,Categoria,Dia,Início,Duração,Local,Descrição,Criado em
+,Reunião,trabalho,Segunda,09:00,1,,,
+,Almoço,pessoal,Segunda,12:00,uma hora,,,
+,Aula,estudo,Feriado,14:00,1,,,
+,Incompleto,pessoal,Terça
+,Revisão,trabalho,Segunda,09:30,1,,,
+").unwrap();
+        
+        let mut organizer = organizer();
+        let report = organizer.import_from_csv(&csv, ImportMode::Append).unwrap();
+        fs::remove_file(&csv).unwrap();
         
-        // Validações
-        self.validate_day(&temp_activity.day)?;
-        self.validate_time(&temp_activity.start_time)?;
+        assert_eq!(report.imported, 1);
+        let records: Vec<usize> = report.rejected.iter().map(|(record, _)| *record).collect();
+        assert_eq!(records, [3, 4, 5, 6]);
+        assert_eq!(report.rejected[0].1, "duração inválida: uma hora");
+        assert_eq!(report.rejected[1].1, "dia inválido: Feriado");
+        assert_eq!(report.rejected[2].1, "esperadas 9 colunas, encontradas 4");
+        assert!(report.rejected[3].1.starts_with("'Revisão': Conflito de horário"));
+    }
+    
+    #[test]
+    fn csv_replace_without_valid_records_keeps_schedule() {
+        let mut organizer = organizer();
+        let id = organizer.add_activity("Reunião", "trabalho", "Segunda", "09:00", 1.0, None, None).unwrap();
+        let header = "ID,Título,Categoria,Dia,Início,Duração,Local,Descrição,Criado em\n";
         
-        if !self.categories.contains_key(&temp_activity.category) {
-            return Err(format!("Categoria '{}' não existe", temp_activity.category));
+        for content in [format!("{},Aula,estudo,Feriado,14:00,1,,,\n", header), header.to_string()] {
+            let csv = temp_path("importar.csv");
+            fs::write(&csv, content).unwrap();
+            let report = organizer.import_from_csv(&csv, ImportMode::Replace).unwrap();
+            fs::remove_file(&csv).unwrap();
+            
+            assert_eq!(report.imported, 0);
+            assert!(organizer.get_activity(&id).is_some());
         }
+        assert!(organizer.undo().is_ok());
+        assert!(organizer.undo().is_err(), "importação sem efeito não deve entrar no histórico");
+    }
+    
+    #[test]
+    fn edit_activity_with_validates_and_can_be_undone() {
+        let mut organizer = organizer();
+        let id = organizer.add_activity("Relatório", "trabalho", "Segunda", "09:00", 1.0, None, None).unwrap();
         
-        // Verificar conflitos (excluindo a própria atividade)
-        let original_id = activity.id.clone();
-        let activities_without_current: Vec<_> = self.activities.iter().filter(|a| a.id != original_id).cloned().collect();
-        let temp_organizer = WeeklyOrganizer {
-            activities: activities_without_current,
-            categories: self.categories.clone(),
-            data_file: self.data_file.clone(),
+        let changes = ActivityPatch { effort: Some(Some(4)), priority: Some(Priority::High), tags: Some(vec!["Cliente".to_string()]), ..ActivityPatch::default() };
+        organizer.edit_activity_with(&id, changes).unwrap();
+        let activity = organizer.get_activity(&id).unwrap();
+        assert_eq!((activity.effort, activity.priority), (Some(4), Priority::High));
+        assert_eq!(activity.tags, normalize_tags(&["Cliente".to_string()]));
+        
+        // Inválida: nada muda
+        let result = organizer.edit_activity_with(&id, ActivityPatch { effort: Some(Some(9)), ..ActivityPatch::default() });
+        assert!(matches!(result, Err(WeeklyError::EffortOutOfRange(9))));
+        assert_eq!(organizer.get_activity(&id).unwrap().effort, Some(4));
+        
+        organizer.undo().unwrap();
+        let activity = organizer.get_activity(&id).unwrap();
+        assert_eq!((activity.effort, activity.priority), (None, Priority::default()));
+        assert!(activity.tags.is_empty());
+    }
+    
+    #[test]
+    fn set_priority_and_set_tags_are_undoable() {
+        let mut organizer = organizer();
+        let id = organizer.add_activity("Relatório", "trabalho", "Segunda", "09:00", 1.0, None, None).unwrap();
+        organizer.set_priority(&id, Priority::High).unwrap();
+        organizer.set_tags(&id, &["urgente".to_string()]).unwrap();
+        
+        organizer.undo().unwrap();
+        assert!(organizer.get_activity(&id).unwrap().tags.is_empty());
+        assert_eq!(organizer.get_activity(&id).unwrap().priority, Priority::High);
+        organizer.undo().unwrap();
+        assert_eq!(organizer.get_activity(&id).unwrap().priority, Priority::default());
+        
+        assert_eq!(organizer.set_priority("inexistente", Priority::Low), Err("Atividade não encontrada".to_string()));
+    }
+    
+    #[test]
+    fn removal_impact_lists_only_waitlist_items_the_removal_frees() {
+        let mut organizer = organizer();
+        organizer.set_working_hours(9, 11).unwrap();
+        let id = organizer.add_activity("Reunião", "trabalho", "Terça", "09:00", 1.0, None, None).unwrap();
+        organizer.add_activity("Relatório", "trabalho", "Terça", "10:00", 1.0, None, None).unwrap();
+        
+        let waiting = |title: &str, day: Weekday| ActivityParams {
+            title: title.to_string(),
+            category: "trabalho".to_string(),
+            day,
+            start_time: "09:00".to_string(),
+            duration: 1.0,
+            location: None,
+            description: None,
         };
+        // A de Terça só cabe sem a reunião; a de Quarta já cabe hoje
+        organizer.add_to_waitlist(waiting("Ligação", Weekday::Tue)).unwrap();
+        organizer.add_to_waitlist(waiting("Revisão", Weekday::Wed)).unwrap();
         
-        if let Some(conflicting) = temp_organizer.check_time_conflict(&temp_activity.day, &temp_activity.start_time, temp_activity.duration) {
-            return Err(format!("Conflito de horário com: '{}'", conflicting.title));
-        }
+        let impact = organizer.removal_impact(&id).unwrap();
+        assert_eq!(impact.waitlist_fits, [0]);
+        assert_eq!(impact.freed_hours, 1.0);
+    }
+    
+    #[test]
+    fn restore_reverts_slot_alignment_and_tentative_blocking() {
+        let mut organizer = organizer();
+        let snapshot = organizer.snapshot();
         
-        // Aplicar mudanças
-        if let Some(t) = title { activity.title = t.to_string(); }
-        if let Some(c) = category { activity.category = c.to_string(); }
-        if let Some(d) = day { activity.day = d.to_string(); }
-        if let Some(st) = start_time { activity.start_time = st.to_string(); }
-        if let Some(dur) = duration { activity.duration = dur; }
-        if let Some(loc) = location { activity.location = Some(loc); }
-        if let Some(desc) = description { activity.description = Some(desc); }
+        organizer.set_slot_alignment(false);
+        organizer.set_tentative_blocks(false);
+        organizer.add_activity("Ligação", "trabalho", "Quinta", "08:15", 0.5, None, None).unwrap();
+        
+        organizer.restore(snapshot);
+        assert!(organizer.align_to_slots);
+        assert!(organizer.tentative_blocks);
+        let result = organizer.add_activity("Ligação", "trabalho", "Quinta", "08:15", 0.5, None, None);
+        assert!(matches!(result, Err(WeeklyError::UnalignedTime { .. })));
+    }
+    
+    #[test]
+    fn snap_to_slots_rounds_only_misaligned_times() {
+        let mut organizer = organizer();
+        organizer.set_working_hours(0, 24).unwrap();
+        let late = organizer.add_activity("Plantão", "trabalho", "Segunda", "23:00", 0.5, None, None).unwrap();
+        let early = organizer.add_activity("Corrida", "exercicio", "Segunda", "05:00", 0.5, None, None).unwrap();
+        organizer.set_slot_alignment(false);
+        let off = organizer.add_activity("Ligação", "trabalho", "Segunda", "09:20", 0.5, None, None).unwrap();
+        let tie = organizer.add_activity("Café", "pessoal", "Segunda", "11:15", 0.5, None, None).unwrap();
+        let midnight = organizer.add_activity("Leitura", "estudo", "Terça", "23:50", 0.5, None, None).unwrap();
+        // Fora do expediente, mas alinhados: continuam onde estão
+        organizer.set_working_hours(6, 22).unwrap();
+        
+        let report = organizer.snap_to_slots();
+        assert_eq!(report.adjusted, [off.as_str(), tie.as_str(), midnight.as_str()]);
+        assert!(report.conflicts.is_empty());
+        
+        let start = |id: &str| organizer.get_activity(id).unwrap().start_time.clone();
+        assert_eq!((start(&late), start(&early)), ("23:00".to_string(), "05:00".to_string()));
+        assert_eq!((start(&off), start(&tie), start(&midnight)), ("09:30".to_string(), "11:00".to_string(), "23:30".to_string()));
+        assert!(organizer.snap_to_slots().adjusted.is_empty());
+    }
+    
+    #[test]
+    fn snap_to_slots_reports_new_conflicts() {
+        let mut organizer = organizer();
+        organizer.set_slot_minutes(15).unwrap();
+        let first = organizer.add_activity("Reunião", "trabalho", "Quarta", "09:00", 0.75, None, None).unwrap();
+        let second = organizer.add_activity("Revisão", "trabalho", "Quarta", "09:45", 0.5, None, None).unwrap();
         
-        // Salvar
-        if let Err(e) = self.save_data() {
-            println!("Aviso: Erro ao salvar dados: {}", e);
-        }
+        // Com slots de 30 minutos, 09:45 volta para 09:30, dentro da reunião (09:00–09:45)
+        organizer.set_slot_minutes(30).unwrap();
+        let report = organizer.snap_to_slots();
+        assert_eq!(report.adjusted, [second.as_str()]);
+        assert_eq!(report.conflicts, [(second, first)]);
+    }
+    
+    #[test]
+    fn cancelled_activities_stay_out_of_aggregates_and_search() {
+        let mut organizer = organizer();
+        let kept = organizer.add_activity("Relatório", "trabalho", "Segunda", "09:00", 1.0, None, None).unwrap();
+        organizer.add_activity("Revisão", "trabalho", "Segunda", "11:00", 1.0, None, None).unwrap();
+        let signature = organizer.shape_signature();
         
-        Ok(())
+        let cancelled = organizer.add_activity("Relatório extra", "trabalho", "Segunda", "10:00", 1.0, None, None).unwrap();
+        organizer.set_effort(&cancelled, Some(5)).unwrap();
+        organizer.set_status(&cancelled, ActivityStatus::Cancelled).unwrap();
+        
+        assert_eq!(organizer.total_effort(), 0);
+        assert_eq!(organizer.shape_signature(), signature);
+        assert!((organizer.routine_consistency("trabalho") - 0.5).abs() < 1e-4);
+        let found: Vec<&str> = organizer.search_activities("relatório").iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(found, [kept.as_str()]);
+        assert_eq!(organizer.search_activities_regex("extra").unwrap().len(), 0);
+        // O intervalo entre 10:00 e 11:00 continua livre para as vizinhas
+        assert_eq!(organizer.neighbor_gaps(&kept), (None, Some(60)));
+        assert_eq!(organizer.cancelled_activities().len(), 1);
     }
     
-    // Remover atividade
-    pub fn remove_activity(&mut self, id: &str) -> Result<(), String> {
-        let initial_len = self.activities.len();
-        self.activities.retain(|a| a.id != id);
+    #[test]
+    fn removal_returns_activities_scheduled_from_waitlist() {
+        let mut organizer = organizer();
+        let id = organizer.add_activity("Reunião", "trabalho", "Terça", "09:00", 1.0, None, None).unwrap();
+        organizer.add_to_waitlist(ActivityParams {
+            title: "Ligação".to_string(),
+            category: "trabalho".to_string(),
+            day: Weekday::Tue,
+            start_time: "09:00".to_string(),
+            duration: 1.0,
+            location: None,
+            description: None,
+        }).unwrap();
         
-        if self.activities.len() == initial_len {
-            return Err("Atividade não encontrada".to_string());
-        }
+        let scheduled = organizer.remove_activity(&id).unwrap();
+        assert_eq!(scheduled.len(), 1);
+        let placed = organizer.get_activity(&scheduled[0]).unwrap();
+        assert_eq!((placed.title.as_str(), placed.start_time.as_str()), ("Ligação", "09:00"));
+        assert!(organizer.waitlist().is_empty());
         
-        // Salvar
-        if let Err(e) = self.save_data() {
-            println!("Aviso: Erro ao salvar dados: {}", e);
-        }
+        assert!(organizer.remove_activity(&id).is_err());
+    }
+    
+    #[test]
+    fn setters_clear_optional_fields_and_are_undoable() {
+        let mut organizer = organizer();
+        let id = organizer.add_activity("Relatório", "trabalho", "Segunda", "09:00", 1.0, None, None).unwrap();
+        organizer.set_effort(&id, Some(3)).unwrap();
+        organizer.set_reminder(&id, Some(15)).unwrap();
         
-        Ok(())
+        organizer.set_effort(&id, None).unwrap();
+        organizer.set_reminder(&id, None).unwrap();
+        let activity = organizer.get_activity(&id).unwrap();
+        assert_eq!((activity.effort, activity.reminder_minutes), (None, None));
+        
+        organizer.undo().unwrap();
+        organizer.undo().unwrap();
+        let activity = organizer.get_activity(&id).unwrap();
+        assert_eq!((activity.effort, activity.reminder_minutes), (Some(3), Some(15)));
+        
+        assert!(organizer.set_effort(&id, Some(6)).is_err());
+        assert_eq!(organizer.set_reminder("inexistente", Some(5)), Err("Atividade não encontrada".to_string()));
     }
     
-    // Listar atividades de um dia
-    pub fn get_activities_by_day(&self, day: &str) -> Vec<&Activity> {
-        let mut activities: Vec<&Activity> = self.activities.iter()
-            .filter(|a| a.day == day)
-            .collect();
+    #[test]
+    fn auto_schedule_without_items_changes_nothing() {
+        let mut organizer = organizer();
+        organizer.save_data().unwrap();
         
-        // Ordenar por horário
-        activities.sort_by(|a, b| a.start_time.cmp(&b.start_time));
-        activities
+        assert_eq!(organizer.auto_schedule(Vec::new(), PackStrategy::EarliestFit), Ok(Vec::new()));
+        assert!(!organizer.has_unsaved_changes());
+        assert!(organizer.undo().is_err());
     }
     
-    // Obter todas as atividades ordenadas
-    pub fn get_all_activities(&self) -> Vec<&Activity> {
-        let mut activities: Vec<&Activity> = self.activities.iter().collect();
-        activities.sort_by(|a, b| {
-            let day_order = ["Segunda", "Terça", "Quarta", "Quinta", "Sexta", "Sábado", "Domingo"];
-            let a_day_idx = day_order.iter().position(|&d| d == a.day).unwrap_or(7);
-            let b_day_idx = day_order.iter().position(|&d| d == b.day).unwrap_or(7);
-            
-            a_day_idx.cmp(&b_day_idx)
-                .then_with(|| a.start_time.cmp(&b.start_time))
-        });
-        activities
+    #[test]
+    fn dependencies_are_ordered_within_the_same_day() {
+        let mut organizer = organizer();
+        let prep = organizer.add_activity("Preparação", "trabalho", "Quarta", "09:00", 1.0, None, None).unwrap();
+        let meeting = organizer.add_activity("Reunião", "trabalho", "Quarta", "08:00", 1.0, None, None).unwrap();
+        
+        // Mesmo dia: a reunião às 08:00 começa antes de a preparação (09:00–10:00) terminar
+        let result = organizer.set_dependency(&meeting, Some(&prep));
+        assert!(result.unwrap_err().contains("Preparação"));
+        
+        // Em outro dia, qualquer ordem vale
+        organizer.move_activity(&meeting, "Terça", "08:00").unwrap();
+        organizer.set_dependency(&meeting, Some(&prep)).unwrap();
+        organizer.move_activity(&meeting, "Quinta", "08:00").unwrap();
+        assert!(organizer.dependency_violations().is_empty());
+        
+        // De volta à Quarta, antes da preparação: rejeitado
+        assert!(organizer.move_activity(&meeting, "Quarta", "08:00").is_err());
+        organizer.move_activity(&meeting, "Quarta", "10:00").unwrap();
     }
     
-    // Calcular estatísticas semanais
-    pub fn calculate_weekly_stats(&self) -> WeeklyStats {
-        let mut stats = WeeklyStats {
-            total_time: 0.0,
-            by_category: HashMap::new(),
-            by_day: HashMap::new(),
-            activity_count: self.activities.len(),
-        };
+    #[test]
+    fn write_atomically_replaces_the_file() {
+        let path = temp_path("dados.json");
+        fs::write(&path, "antigo").unwrap();
         
-        for activity in &self.activities {
-            // Tempo total
-            stats.total_time += activity.duration;
-            
-            // Por categoria
-            *stats.by_category.entry(activity.category.clone()).or_insert(0.0) += activity.duration;
-            
-            // Por dia
-            *stats.by_day.entry(activity.day.clone()).or_insert(0.0) += activity.duration;
-        }
+        write_atomically(&path, "novo").unwrap();
         
-        stats
+        assert_eq!(fs::read_to_string(&path).unwrap(), "novo");
+        assert!(!Path::new(&format!("{}.tmp", path)).exists());
+        fs::remove_file(&path).unwrap();
     }
     
-    // Formatar tempo
-    pub fn format_time(hours: f32) -> String {
-        if hours < 1.0 {
-            format!("{}min", (hours * 60.0).round() as i32)
-        } else if hours == 1.0 {
-            "1h".to_string()
-        } else if hours.fract() == 0.0 {
-            format!("{}h", hours as i32)
-        } else {
-            let whole_hours = hours.floor() as i32;
-            let minutes = ((hours - whole_hours as f32) * 60.0).round() as i32;
-            format!("{}h {}min", whole_hours, minutes)
-        }
+    #[test]
+    fn failed_temp_write_keeps_previous_file() {
+        let path = temp_path("dados.json");
+        fs::write(&path, "conteúdo bom").unwrap();
+        // Um diretório no lugar do .tmp faz a escrita falhar antes de tocar no original
+        fs::create_dir(format!("{}.tmp", path)).unwrap();
+        
+        assert!(write_atomically(&path, "conteúdo novo").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "conteúdo bom");
+        
+        fs::remove_dir(format!("{}.tmp", path)).unwrap();
+        fs::remove_file(&path).unwrap();
     }
     
-    // Exibir grade semanal
-    pub fn display_weekly_grid(&self) {
-        let days = ["Segunda", "Terça", "Quarta", "Quinta", "Sexta", "Sábado", "Domingo"];
-        let time_slots = Self::generate_time_slots();
+    #[test]
+    fn failed_rename_keeps_target_and_removes_temp() {
+        // Renomear um arquivo por cima de um diretório não vazio sempre falha
+        let path = temp_path("dados");
+        fs::create_dir(&path).unwrap();
+        fs::write(format!("{}/dentro.txt", path), "intacto").unwrap();
         
-        println!("\n╔═══════════════════════════════════════════════════════════════════════════════════════════════════════════════════╗");
-        println!("║                                              ORGANIZADOR SEMANAL                                                     ║");
-        println!("╠═══════════╦══════════════╦══════════════╦══════════════╦══════════════╦══════════════╦══════════════╦══════════════╣");
-        print!("║   HORÁRIO ║");
-        for day in &days {
-            print!(" {:^12} ║", day);
-        }
-        println!();
-        println!("╠═══════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╣");
+        let error = write_atomically(&path, "conteúdo novo").unwrap_err();
         
-        for (i, time) in time_slots.iter().enumerate() {
-            // Mostrar apenas horários completos
-            if time.ends_with(":00") {
-                print!("║ {:^9} ║", time);
-            } else {
-                print!("║ {:^9} ║", "");
-            }
-            
-            for day in &days {
-                let activity = self.activities.iter().find(|a| {
-                    a.day == *day && a.start_time == *time
-                });
-                
-                match activity {
-                    Some(act) => {
-                        let short_title = if act.title.len() > 12 {
-                            format!("{}...", &act.title[..9])
-                        } else {
-                            act.title.clone()
-                        };
-                        print!(" {:^12} ║", short_title);
-                    },
-                    None => print!(" {:^12} ║", ""),
-                }
-            }
-            println!();
-            
-            // Linha separadora a cada hora
-            if time.ends_with(":30") {
-                if i < time_slots.len() - 1 {
-                    println!("╠═══════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╬══════════════╣");
-                }
-            }
-        }
+        assert!(error.to_string().contains("Não foi possível substituir"));
+        assert_eq!(fs::read_to_string(format!("{}/dentro.txt", path)).unwrap(), "intacto");
+        assert!(!Path::new(&format!("{}.tmp", path)).exists());
+        fs::remove_dir_all(&path).unwrap();
+    }
+    
+    // Arquivo de dados válido cortado ao meio, como numa gravação interrompida
+    fn truncated_data_file() -> (String, Vec<u8>) {
+        let path = temp_path("dados.json");
+        let mut organizer = WeeklyOrganizer::open(&path).unwrap();
+        organizer.add_activity("Reunião", "trabalho", "Segunda", "09:00", 1.0, None, None).unwrap();
         
-        println!("╚═══════════╩══════════════╩══════════════╩══════════════╩══════════════╩══════════════╩══════════════╩══════════════╝");
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        fs::write(&path, &bytes).unwrap();
+        (path, bytes)
     }
     
-    // Exibir estatísticas
-    pub fn display_stats(&self) {
-        let stats = self.calculate_weekly_stats();
+    #[test]
+    fn open_rejects_truncated_json_without_touching_it() {
+        let (path, bytes) = truncated_data_file();
         
-        println!("\n╔══════════════════════════════════════════════════════════════════╗");
-        println!("║                      ESTATÍSTICAS SEMANAIS                      ║");
-        println!("╠══════════════════════════════════════════════════════════════════╣");
-        println!("║ Total de atividades: {:^42} ║", stats.activity_count);
-        println!("║ Tempo total semanal: {:^42} ║", Self::format_time(stats.total_time));
-        println!("╠══════════════════════════════════════════════════════════════════╣");
-        println!("║                      POR CATEGORIA                              ║");
-        println!("╠══════════════════════════════════════════════════════════════════╣");
+        let error = WeeklyOrganizer::open(&path).err().unwrap();
         
-        let mut category_stats: Vec<_> = stats.by_category.iter().collect();
-        category_stats.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap());
+        assert!(error.to_string().contains("Não foi possível ler"));
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+        fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    #[should_panic(expected = "Não foi possível carregar")]
+    #[allow(deprecated)]
+    fn new_panics_on_truncated_json() {
+        let (path, _) = truncated_data_file();
+        WeeklyOrganizer::new(&path);
+    }
+    
+    #[test]
+    fn format_time_common_durations() {
+        assert_eq!(WeeklyOrganizer::format_time(0.5), "30min");
+        assert_eq!(WeeklyOrganizer::format_time(1.0), "1h");
+        assert_eq!(WeeklyOrganizer::format_time(1.5), "1h 30min");
+        assert_eq!(WeeklyOrganizer::format_time(2.0), "2h");
+        assert_eq!(WeeklyOrganizer::format_time(0.25), "15min");
+    }
+    
+    #[test]
+    fn format_time_tolerates_float_error() {
+        // 0.1 * 10 não é exatamente 1.0 em f32
+        assert_eq!(WeeklyOrganizer::format_time((0..10).map(|_| 0.1f32).sum()), "1h");
+        assert_eq!(WeeklyOrganizer::format_time(1.0 / 3.0), "20min");
+    }
+    
+    // Texto das células da grade em um dia, a partir do horário informado
+    fn grid_column(organizer: &WeeklyOrganizer, day: Weekday, from: &str, count: usize) -> Vec<String> {
+        let column = day.num_days_from_monday() as usize;
+        organizer.grid_rows().into_iter()
+            .skip_while(|(time, _)| time != from)
+            .take(count)
+            .map(|(_, cells)| cells[column].0.clone())
+            .collect()
+    }
+    
+    #[test]
+    fn grid_fills_every_slot_an_activity_spans() {
+        let mut organizer = organizer();
+        organizer.add_activity("Estudo", "estudo", "Quarta", "09:00", 1.5, None, None).unwrap();
         
-        for (category_key, time) in category_stats {
-            if let Some(category) = self.categories.get(category_key) {
-                let percentage = if stats.total_time > 0.0 {
-                    (time / stats.total_time) * 100.0
-                } else {
-                    0.0
-                };
-                println!("║ {:20} │ {:>12} │ {:>6.1}% ║", 
-                    category.name, 
-                    Self::format_time(*time),
-                    percentage
-                );
-            }
-        }
+        assert_eq!(grid_column(&organizer, Weekday::Wed, "08:30", 5), ["", "Estudo", "│", "│", ""]);
+        assert!(organizer.slot_continues(Weekday::Wed, 10 * 60));
+        assert!(!organizer.slot_continues(Weekday::Wed, 10 * 60 + 30));
+    }
+    
+    #[test]
+    fn grid_continues_past_midnight_into_next_day() {
+        let mut organizer = organizer();
+        organizer.set_working_hours(0, 24).unwrap();
+        organizer.add_activity("Plantão", "trabalho", "Domingo", "23:00", 2.0, None, None).unwrap();
         
-        println!("╠══════════════════════════════════════════════════════════════════╣");
-        println!("║                        POR DIA                                  ║");
-        println!("╠══════════════════════════════════════════════════════════════════╣");
+        assert_eq!(grid_column(&organizer, Weekday::Sun, "23:00", 2), ["Plantão", "│"]);
+        assert_eq!(grid_column(&organizer, Weekday::Mon, "00:00", 3), ["│", "│", ""]);
+    }
+    
+    #[test]
+    fn activity_past_midnight_conflicts_with_next_day() {
+        let mut organizer = organizer();
+        organizer.set_working_hours(0, 24).unwrap();
+        organizer.add_activity("Plantão", "trabalho", "Segunda", "23:30", 1.5, None, None).unwrap();
         
-        let days = ["Segunda", "Terça", "Quarta", "Quinta", "Sexta", "Sábado", "Domingo"];
-        for day in &days {
-            let day_time = stats.by_day.get(*day).unwrap_or(&0.0);
-            let percentage = if stats.total_time > 0.0 {
-                (day_time / stats.total_time) * 100.0
-            } else {
-                0.0
-            };
-            println!("║ {:20} │ {:>12} │ {:>6.1}% ║", 
-                day, 
-                Self::format_time(*day_time),
-                percentage
-            );
-        }
+        let result = organizer.add_activity("Café", "pessoal", "Terça", "00:30", 1.0, None, None);
+        assert!(matches!(result, Err(WeeklyError::TimeConflict { ref with }) if with == &["Plantão"]));
+        // Termina à 01:00 de Terça: o horário seguinte está livre
+        organizer.add_activity("Café", "pessoal", "Terça", "01:00", 1.0, None, None).unwrap();
+    }
+    
+    #[test]
+    fn sunday_wraps_into_monday() {
+        let mut organizer = organizer();
+        organizer.set_working_hours(0, 24).unwrap();
+        organizer.add_activity("Madrugada", "pessoal", "Segunda", "00:30", 1.0, None, None).unwrap();
         
-        println!("╚══════════════════════════════════════════════════════════════════╝");
+        // Vale nos dois sentidos: quem começa no Domingo esbarra no que já está na Segunda
+        let result = organizer.add_activity("Plantão", "trabalho", "Domingo", "23:00", 2.0, None, None);
+        assert!(matches!(result, Err(WeeklyError::TimeConflict { ref with }) if with == &["Madrugada"]));
+        
+        organizer.add_activity("Plantão", "trabalho", "Domingo", "23:00", 1.5, None, None).unwrap();
+        assert_eq!(organizer.overlapping_time(), 0.0);
     }
     
-    // Salvar dados em arquivo JSON
-    pub fn save_data(&self) -> Result<(), Box<dyn std::error::Error>> {
-        #[derive(Serialize)]
-        struct SaveData {
-            activities: Vec<Activity>,
-            categories: HashMap<String, Category>,
-        }
+    #[test]
+    fn grid_truncates_accented_titles_by_character() {
+        let mut organizer = organizer();
+        // O "ã" ocupa os bytes 8 e 9: cortar em 9 bytes cairia no meio dele
+        organizer.add_activity("Avaliação geral", "trabalho", "Sexta", "14:00", 1.0, None, None).unwrap();
+        organizer.add_activity("Reunião às 15h", "trabalho", "Sexta", "16:00", 1.0, None, None).unwrap();
+        organizer.add_activity("Ação rápida", "pessoal", "Sexta", "17:00", 1.0, None, None).unwrap();
         
-        let data = SaveData {
-            activities: self.activities.clone(),
-            categories: self.categories.clone(),
-        };
+        let column = grid_column(&organizer, Weekday::Fri, "14:00", 7);
+        assert_eq!(column[0], "Avaliação...");
+        assert_eq!(column[4], "Reunião à...");
+        // Cabe na largura em caracteres, embora tenha mais de 12 bytes
+        assert_eq!(column[6], "Ação rápida");
+        assert!(column.iter().all(|cell| cell.chars().count() <= 12));
+    }
+    
+    #[test]
+    fn durations_must_fill_whole_slots() {
+        let mut organizer = organizer();
+        organizer.add_activity("Alongamento", "saude", "Segunda", "08:00", 0.5, None, None).unwrap();
+        organizer.add_activity("Estudo", "estudo", "Segunda", "09:00", 2.0, None, None).unwrap();
         
-        let json = serde_json::to_string_pretty(&data)?;
-        fs::write(&self.data_file, json)?;
-        Ok(())
+        let result = organizer.add_activity("Leitura", "estudo", "Segunda", "12:00", 0.75, None, None);
+        assert!(matches!(result, Err(WeeklyError::DurationNotMultipleOfSlot { slot_minutes: 30, .. })));
+        assert_eq!(organizer.get_activities_by_day("Segunda").len(), 2);
     }
     
-    // Carregar dados do arquivo JSON
-    pub fn load_data(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        #[derive(Deserialize)]
-        struct SaveData {
-            activities: Vec<Activity>,
-            categories: HashMap<String, Category>,
-        }
+    #[test]
+    fn start_times_must_fall_on_a_slot() {
+        let mut organizer = organizer();
+        organizer.add_activity("Reunião", "trabalho", "Terça", "08:00", 0.5, None, None).unwrap();
+        organizer.add_activity("Revisão", "trabalho", "Terça", "08:30", 0.5, None, None).unwrap();
         
-        let content = fs::read_to_string(&self.data_file)?;
-        let data: SaveData = serde_json::from_str(&content)?;
+        let result = organizer.add_activity("Ligação", "trabalho", "Terça", "08:15", 0.5, None, None);
+        assert!(matches!(result, Err(WeeklyError::UnalignedTime { ref time, slot_minutes: 30 }) if time == "08:15"));
         
-        self.activities = data.activities;
-        self.categories.extend(data.categories);
+        // Sem alinhamento, horários livres são aceitos
+        organizer.set_slot_alignment(false);
+        organizer.add_activity("Ligação", "trabalho", "Terça", "10:15", 0.5, None, None).unwrap();
+    }
+    
+    #[test]
+    fn category_percentages_sum_to_100() {
+        let mut organizer = organizer();
+        organizer.add_activity("Relatório", "trabalho", "Quinta", "09:00", 1.0, None, None).unwrap();
+        organizer.add_activity("Mercado", "pessoal", "Quinta", "11:00", 1.0, None, None).unwrap();
+        organizer.add_activity("Curso", "estudo", "Quinta", "14:00", 1.0, None, None).unwrap();
         
-        Ok(())
+        let percentages: Vec<f32> = organizer.category_percentages(&organizer.calculate_weekly_stats())
+            .into_iter()
+            .map(|(_, percentage)| percentage)
+            .collect();
+        // Três terços: 33,4 + 33,3 + 33,3, não 99,9
+        assert_eq!(percentages.iter().filter(|p| (**p - 33.3).abs() < 1e-4).count(), 2);
+        assert!((percentages.iter().sum::<f32>() - 100.0).abs() < 1e-3);
     }
     
-    // Exportar para CSV
-    pub fn export_to_csv(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut content = String::new();
-        content.push_str("ID,Título,Categoria,Dia,Horário,Duração(h),Local,Descrição,Criado em\n");
+    #[test]
+    fn apportion_percentages_hands_leftover_to_largest_remainders() {
+        assert_eq!(apportion_percentages(&[1.0, 1.0, 1.0], 0), [34.0, 33.0, 33.0]);
+        assert_eq!(apportion_percentages(&[2.0, 1.0, 1.0, 1.0, 1.0, 1.0], 0), [29.0, 15.0, 14.0, 14.0, 14.0, 14.0]);
+        assert_eq!(apportion_percentages(&[0.0, 0.0], 1), [0.0, 0.0]);
+    }
+    
+    // Medição do índice por dia com 500 atividades; rodar com
+    // `cargo test --release -- --ignored --nocapture benchmark_500_activities`
+    #[test]
+    #[ignore = "benchmark: lento em build de debug e só imprime tempos"]
+    fn benchmark_500_activities() {
+        use std::time::Instant;
         
-        for activity in &self.activities {
-            content.push_str(&format!(
-                "{},{},{},{},{},{},{},{},{}\n",
-                activity.id,
-                activity.title.replace(",", ";"),
-                activity.category,
-                activity.day,
-                activity.start_time,
-                activity.duration,
-                activity.location.as_ref().unwrap_or(&"".to_string()).replace(",", ";"),
-                activity.description.as_ref().unwrap_or(&"".to_string()).replace(",", ";"),
-                activity.created_at.format("%Y-%m-%d %H:%M:%S")
-            ));
+        let days = ["Segunda", "Terça", "Quarta", "Quinta", "Sexta", "Sábado", "Domingo"];
+        let mut organizer = organizer();
+        organizer.set_working_hours(0, 24).unwrap();
+        organizer.set_slot_minutes(15).unwrap();
+        
+        // Slots de 15 minutos preenchidos em ordem: Segunda a Sexta cheias, Sábado até 04:45
+        let times: Vec<String> = organizer.generate_time_slots();
+        let slots: Vec<(&str, &String)> = days.iter()
+            .flat_map(|day| times.iter().map(move |time| (*day, time)))
+            .take(500)
+            .collect();
+        
+        let started = Instant::now();
+        for (day, time) in &slots {
+            organizer.add_activity("Tarefa", "trabalho", day, time, 0.25, None, None).unwrap();
         }
+        let inserts = started.elapsed();
+        assert_eq!(organizer.activities.len(), 500);
         
-        fs::write(filename, content)?;
-        Ok(())
-    }
-    
-    // Buscar atividades
-    pub fn search_activities(&self, query: &str) -> Vec<&Activity> {
-        let query_lower = query.to_lowercase();
-        self.activities.iter()
-            .filter(|activity| {
-                activity.title.to_lowercase().contains(&query_lower) ||
-                activity.category.to_lowercase().contains(&query_lower) ||
-                activity.location.as_ref().map_or(false, |loc| loc.to_lowercase().contains(&query_lower)) ||
-                activity.description.as_ref().map_or(false, |desc| desc.to_lowercase().contains(&query_lower))
-            })
-            .collect()
+        let started = Instant::now();
+        let by_day: usize = (0..20_000).map(|i| organizer.get_activities_by_day(days[i % 7]).len()).sum();
+        let lookups = started.elapsed();
+        
+        let started = Instant::now();
+        let conflicts: usize = (0..20_000).map(|i| organizer.find_conflicts(days[i % 7], "12:00", 1.0).len()).sum();
+        let conflict_checks = started.elapsed();
+        
+        eprintln!("500 inserções: {:?}", inserts);
+        eprintln!("get_activities_by_day x20000: {:?} ({} resultados)", lookups, by_day);
+        eprintln!("find_conflicts x20000: {:?} ({} conflitos)", conflict_checks, conflicts);
     }
 }
 
 // Interface de linha de comando
 pub struct CLI {
     organizer: WeeklyOrganizer,
+    explicit_save: bool, // Alterações só são gravadas em "Salvar" ou ao sair
 }
 
 impl CLI {
     pub fn new(data_file: &str) -> Self {
-        CLI {
-            organizer: WeeklyOrganizer::new(data_file),
+        // Um arquivo corrompido encerra o programa em vez de ser sobrescrito com a agenda vazia
+        let organizer = match WeeklyOrganizer::open(data_file) {
+            Ok(organizer) => organizer,
+            Err(e) => {
+                println!("❌ Erro ao carregar dados: {}", e);
+                std::process::exit(1);
+            }
+        };
+        
+        let mut cli = CLI {
+            organizer,
+            explicit_save: false,
+        };
+        
+        // "--color" força as cores mesmo sem terminal (ex.: `| less -R`)
+        if std::env::args().any(|arg| arg == "--color") {
+            cli.organizer.set_ansi_colors(true);
         }
+        cli
+    }
+    
+    // CLI em modo de salvamento explícito
+    pub fn with_explicit_save(data_file: &str) -> Self {
+        let mut cli = Self::new(data_file);
+        cli.explicit_save = true;
+        cli.organizer.set_autosave(false);
+        cli
     }
     
     pub fn run(&mut self) {
@@ -523,7 +5158,17 @@ impl CLI {
                 "7" => self.search_activities_interactive(),
                 "8" => self.export_csv_interactive(),
                 "9" => self.list_categories(),
+                "i" | "I" => self.import_csv_interactive(),
+                "l" | "L" => self.clean_categories_interactive(),
+                "a" | "A" => self.schedules_interactive(),
+                "u" | "U" => self.undo_interactive(),
+                "r" | "R" => self.redo_interactive(),
+                "s" | "S" => self.save_interactive(),
+                "d" | "D" if self.explicit_save => self.discard_changes_interactive(),
                 "0" => {
+                    for day in self.organizer.fully_booked_days() {
+                        println!("⚠️  {} não tem nenhum horário livre na grade. Confira se não há engano.", day);
+                    }
                     println!("Salvando dados...");
                     if let Err(e) = self.organizer.save_data() {
                         println!("Erro ao salvar: {}", e);
@@ -551,8 +5196,146 @@ impl CLI {
         println!("│  7. Buscar atividades                                           │");
         println!("│  8. Exportar para CSV                                           │");
         println!("│  9. Listar categorias                                           │");
+        println!("│  I. Importar de CSV                                             │");
+        println!("│  L. Limpar categorias não usadas                                │");
+        println!("│  A. Agendas (criar, trocar, excluir)                            │");
+        println!("│  U. Desfazer                                                    │");
+        println!("│  R. Refazer                                                     │");
+        if self.explicit_save {
+            println!("│  S. Salvar alterações                                           │");
+            println!("│  D. Descartar alterações não salvas                             │");
+        }
         println!("│  0. Sair                                                        │");
         println!("└──────────────────────────────────────────────────────────────────┘");
+        if self.explicit_save && self.organizer.has_unsaved_changes() {
+            println!("  ● Há alterações não salvas");
+        }
+    }
+    
+    fn import_csv_interactive(&mut self) {
+        let filename = self.get_user_input("Arquivo CSV para importar: ");
+        let mode = self.get_user_input("Substituir as atividades atuais? (s/n): ");
+        let mode = if mode.eq_ignore_ascii_case("s") { ImportMode::Replace } else { ImportMode::Append };
+        
+        match self.organizer.import_from_csv(&filename, mode) {
+            Ok(report) => {
+                println!("✅ {} atividade(s) importada(s).", report.imported);
+                for (record, reason) in &report.rejected {
+                    println!("⚠️  Registro {} ignorado: {}", record, reason);
+                }
+            }
+            Err(e) => println!("❌ Erro ao importar: {}", e),
+        }
+    }
+    
+    fn clean_categories_interactive(&mut self) {
+        let unused: Vec<String> = self.organizer.unused_categories().into_iter().cloned().collect();
+        if unused.is_empty() {
+            println!("Todas as categorias estão em uso.");
+            return;
+        }
+        
+        println!("\nCategorias sem nenhuma atividade:");
+        for key in &unused {
+            let name = self.organizer.categories.get(key).map_or(key.as_str(), |c| c.name.as_str());
+            println!("  • {} ({})", name, key);
+        }
+        
+        let confirm = self.get_user_input("Remover essas categorias? (s/n): ");
+        if !confirm.eq_ignore_ascii_case("s") {
+            return;
+        }
+        
+        let removed = self.organizer.remove_unused_categories();
+        println!("✅ {} categoria(s) removida(s).", removed.len());
+    }
+    
+    fn schedules_interactive(&mut self) {
+        println!("\n=== AGENDAS ===");
+        for name in self.organizer.schedule_names() {
+            if name == self.organizer.active_schedule() {
+                println!("  ● {} (ativa)", name);
+            } else {
+                println!("  ○ {}", name);
+            }
+        }
+        
+        println!("\n1. Criar  2. Trocar  3. Excluir  (Enter para voltar)");
+        let result = match self.get_user_input("Opção: ").as_str() {
+            "1" => {
+                let name = self.get_user_input("Nome da nova agenda: ");
+                self.organizer.create_schedule(&name).map(|()| format!("Agenda '{}' criada.", name.trim()))
+            }
+            "2" => {
+                let name = self.get_user_input("Agenda a ativar: ");
+                self.organizer.switch_schedule(&name).map(|()| format!("Agenda ativa: {}", name))
+            }
+            "3" => {
+                let name = self.get_user_input("Agenda a excluir: ");
+                let confirm = self.get_user_input(&format!("Excluir '{}' e todas as suas atividades? (s/n): ", name));
+                if !confirm.eq_ignore_ascii_case("s") {
+                    return;
+                }
+                self.organizer.delete_schedule(&name).map(|()| format!("Agenda '{}' excluída.", name))
+            }
+            _ => return,
+        };
+        
+        match result {
+            Ok(message) => println!("✅ {}", message),
+            Err(e) => println!("❌ {}", e),
+        }
+    }
+    
+    fn undo_interactive(&mut self) {
+        match self.organizer.undo() {
+            Ok(()) => println!("✅ Alteração desfeita."),
+            Err(e) => println!("❌ {}", e),
+        }
+    }
+    
+    fn redo_interactive(&mut self) {
+        match self.organizer.redo() {
+            Ok(()) => println!("✅ Alteração refeita."),
+            Err(e) => println!("❌ {}", e),
+        }
+    }
+    
+    fn save_interactive(&mut self) {
+        match self.organizer.save_data() {
+            Ok(()) => println!("✅ Dados salvos com sucesso!"),
+            Err(e) => println!("❌ Erro ao salvar: {}", e),
+        }
+    }
+    
+    fn discard_changes_interactive(&mut self) {
+        if !self.organizer.has_unsaved_changes() {
+            println!("Não há alterações para descartar.");
+            return;
+        }
+        
+        match self.organizer.unsaved_changes() {
+            Ok(changes) => {
+                for change in changes {
+                    match change {
+                        Change::Added(a) => println!("  + {} ({} {})", a.title, self.organizer.locale().day_name(a.day), a.start_time),
+                        Change::Removed(a) => println!("  - {} ({} {})", a.title, self.organizer.locale().day_name(a.day), a.start_time),
+                        Change::Modified { after, .. } => println!("  ~ {} ({} {})", after.title, self.organizer.locale().day_name(after.day), after.start_time),
+                    }
+                }
+            }
+            Err(e) => println!("Aviso: não foi possível comparar com o arquivo salvo: {}", e),
+        }
+        
+        let confirm = self.get_user_input("Descartar todas as alterações não salvas? (s/n): ");
+        if !confirm.eq_ignore_ascii_case("s") {
+            return;
+        }
+        
+        match self.organizer.discard_changes() {
+            Ok(()) => println!("✅ Alterações descartadas."),
+            Err(e) => println!("❌ Erro ao recarregar dados: {}", e),
+        }
     }
     
     fn get_user_input(&self, prompt: &str) -> String {
@@ -573,10 +5356,23 @@ impl CLI {
         }
         
         self.list_categories();
-        let category = self.get_user_input("Categoria: ");
+        let category = match self.organizer.guess_category(&title) {
+            Some(suggestion) => {
+                let input = self.get_user_input(&format!("Categoria (sugestão: {}, Enter para aceitar): ", suggestion));
+                if input.is_empty() { suggestion } else { input }
+            }
+            None => self.get_user_input("Categoria: "),
+        };
         
-        println!("Dias disponíveis: Segunda, Terça, Quarta, Quinta, Sexta, Sábado, Domingo");
+        println!("Dias disponíveis: {}", self.organizer.locale().day_names().join(", "));
         let day = self.get_user_input("Dia da semana: ");
+        let weekday = match self.organizer.locale().parse_day(&day) {
+            Some(weekday) => weekday,
+            None => {
+                println!("Dia inválido!");
+                return;
+            }
+        };
         
         let start_time = self.get_user_input("Horário de início (HH:MM): ");
         
@@ -595,8 +5391,28 @@ impl CLI {
         let description = self.get_user_input("Descrição (opcional): ");
         let description = if description.is_empty() { None } else { Some(description) };
         
+        let params = ActivityParams {
+            title: title.clone(),
+            category: category.clone(),
+            day: weekday,
+            start_time: start_time.clone(),
+            duration,
+            location: location.clone(),
+            description: description.clone(),
+        };
+        
         match self.organizer.add_activity(&title, &category, &day, &start_time, duration, location, description) {
             Ok(id) => println!("✅ Atividade criada com sucesso! ID: {}", id),
+            Err(e @ WeeklyError::TimeConflict { .. }) => {
+                println!("❌ Erro: {}", e);
+                let answer = self.get_user_input("Colocar na lista de espera? (s/n): ");
+                if answer.eq_ignore_ascii_case("s") {
+                    match self.organizer.add_to_waitlist(params) {
+                        Ok(()) => println!("🕒 Atividade adicionada à lista de espera."),
+                        Err(e) => println!("❌ Erro: {}", e),
+                    }
+                }
+            }
             Err(e) => println!("❌ Erro: {}", e),
         }
     }
@@ -613,8 +5429,13 @@ impl CLI {
         for activity in activities {
             println!("\n┌─────────────────────────────────────────────────────────────");
             println!("│ ID: {}", activity.id);
-            println!("│ 📝 {}", activity.title);
-            println!("│ 📅 {} às {}", activity.day, activity.start_time);
+            let title = self.organizer.colorize(&activity.title, &activity.category);
+            if activity.status == ActivityStatus::Tentative {
+                println!("│ 📝 {} (provisória)", title);
+            } else {
+                println!("│ 📝 {}", title);
+            }
+            println!("│ 📅 {} das {} às {}", self.organizer.locale().day_name(activity.day), activity.start_time, activity.end_time());
             println!("│ ⏱️  Duração: {}", WeeklyOrganizer::format_time(activity.duration));
             println!("│ 🏷️  Categoria: {}", self.organizer.categories.get(&activity.category).map_or(&activity.category, |c| &c.name));
             if let Some(location) = &activity.location {
@@ -623,6 +5444,24 @@ impl CLI {
             if let Some(description) = &activity.description {
                 println!("│ 📄 Descrição: {}", description);
             }
+            if let Some(effort) = activity.effort {
+                println!("│ ⚡ Esforço: {}/5", effort);
+            }
+            if activity.priority != Priority::Normal {
+                println!("│ 🚩 Prioridade: {}", activity.priority.label());
+            }
+            if !activity.tags.is_empty() {
+                println!("│ 🔖 Tags: {}", activity.tags.join(", "));
+            }
+            if let Some(minutes) = activity.reminder_minutes {
+                println!("│ ⏰ Lembrete: {} min antes", minutes);
+            }
+            if let Some(recurrence) = &activity.recurrence {
+                println!("│ 🔁 {}", recurrence.describe());
+            }
+            for note in &activity.notes {
+                println!("│ 🗒️  {} — {}", note.created_at.format("%d/%m %H:%M"), note.text);
+            }
             println!("└─────────────────────────────────────────────────────────────");
         }
     }
@@ -633,7 +5472,7 @@ impl CLI {
         let id = self.get_user_input("ID da atividade para editar: ");
         
         // Verificar se atividade existe
-        let activity = match self.organizer.activities.iter().find(|a| a.id == id) {
+        let activity = match self.organizer.get_activity(&id) {
             Some(act) => act.clone(),
             None => {
                 println!("Atividade não encontrada!");
@@ -650,7 +5489,7 @@ impl CLI {
         let category = self.get_user_input(&format!("Nova categoria ({}): ", activity.category));
         let category = if category.is_empty() { None } else { Some(category.as_str()) };
         
-        let day = self.get_user_input(&format!("Novo dia ({}): ", activity.day));
+        let day = self.get_user_input(&format!("Novo dia ({}): ", self.organizer.locale().day_name(activity.day)));
         let day = if day.is_empty() { None } else { Some(day.as_str()) };
         
         let start_time = self.get_user_input(&format!("Novo horário ({}): ", activity.start_time));